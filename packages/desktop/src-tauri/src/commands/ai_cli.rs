@@ -1,6 +1,12 @@
 use serde::Serialize;
+use std::path::Path;
+use tauri::State;
 use tokio::process::Command;
 
+use super::config_state::ConfigState;
+use super::workspace_settings::resolve_settings;
+use super::worktree::resolve_worktree;
+
 /// Information about a detected AI CLI tool.
 #[derive(Debug, Serialize)]
 pub struct AiToolInfo {
@@ -35,13 +41,39 @@ pub async fn detect_ai_tools() -> Vec<AiToolInfo> {
 
 /// Launch an AI CLI tool in a new terminal window.
 ///
-/// Uses proper argument passing to prevent command injection.
+/// Uses proper argument passing to prevent command injection. With
+/// `worktree` (a path or branch of one of the workspace's worktrees) the
+/// tool runs in that worktree instead, isolating parallel sessions.
+///
+/// Without `tool` the workspace's `aiTool` setting is launched. Without
+/// `args` the workspace's `aiArgs` are passed, if the tool is its `aiTool`.
 #[tauri::command]
 pub async fn launch_ai_cli(
-    tool: String,
+    config_state: State<'_, ConfigState>,
+    tool: Option<String>,
     workspace_path: String,
-    args: Vec<String>,
+    args: Option<Vec<String>>,
+    worktree: Option<String>,
 ) -> Result<u32, String> {
+    let config = config_state.config()?.unwrap_or_default();
+    let settings = resolve_settings(&config, Path::new(&workspace_path));
+    let preferred = settings.ai_tool.map(|tool| tool.value);
+    let tool = tool
+        .or_else(|| preferred.clone())
+        .ok_or("No AI tool given and none is configured for the workspace")?;
+    let args = match args {
+        Some(args) => args,
+        None if preferred.as_deref() == Some(tool.as_str()) => {
+            settings.ai_args.map(|args| args.value).unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let workspace_path = match worktree {
+        Some(worktree) => resolve_worktree(&workspace_path, &worktree).await?,
+        None => workspace_path,
+    };
+
     #[cfg(target_os = "macos")]
     {
         // Use osascript with proper escaping via shell_escape
@@ -0,0 +1,755 @@
+use serde::Serialize;
+use std::collections::HashSet;
+
+use super::git::run_git;
+
+/// Fields read per ref from `git for-each-ref`, separated by NUL.
+const BRANCH_FORMAT: &str = "--format=%(refname)%00%(HEAD)%00%(symref)%00%(upstream:short)%00%(upstream:track,nobracket)%00%(committerdate:iso-strict)%00%(authorname)%00%(objectname)";
+const BRANCH_FIELDS: usize = 8;
+
+/// A git branch entry.
+#[derive(Debug, Serialize)]
+pub struct GitBranch {
+    /// Short name, e.g. `main` or `origin/main` for remote branches.
+    pub name: String,
+    pub is_current: bool,
+    pub is_remote: bool,
+    /// Upstream tracking branch of a local branch.
+    pub upstream: Option<String>,
+    /// Set when the upstream branch no longer exists on the remote.
+    pub upstream_gone: bool,
+    pub ahead: u32,
+    pub behind: u32,
+    pub last_commit_hash: String,
+    pub last_commit_date: String,
+    pub last_commit_author: String,
+    /// Whether the branch is merged into the default branch, when known.
+    pub merged: Option<bool>,
+}
+
+/// Error returned by branch commands.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BranchError {
+    /// Deleting would lose commits; retry with `force`.
+    Unmerged {
+        branch: String,
+    },
+    /// The branch is checked out here or in another worktree.
+    CheckedOut {
+        branch: String,
+    },
+    AlreadyExists {
+        branch: String,
+    },
+    InvalidName {
+        name: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for BranchError {
+    fn from(message: String) -> Self {
+        BranchError::Failed { message }
+    }
+}
+
+/// What `git_checkout` did.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CheckoutResult {
+    /// Local branch now checked out, or `None` for a detached HEAD.
+    pub branch: Option<String>,
+    /// Remote branch a new local tracking branch was created from.
+    pub created_from: Option<String>,
+    /// Outcome of stashing conflicting local changes, if that was needed.
+    pub auto_stash: Option<AutoStashOutcome>,
+}
+
+/// Result of re-applying auto-stashed changes after a checkout.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AutoStashOutcome {
+    Reapplied,
+    /// The changes conflicted with the new branch; the stash is kept.
+    Conflicted {
+        paths: Vec<String>,
+    },
+}
+
+/// Error returned by `git_checkout`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CheckoutError {
+    /// Local changes to these paths would be overwritten; retry with
+    /// `auto_stash`, or commit or discard them first.
+    LocalChanges {
+        paths: Vec<String>,
+    },
+    NotFound {
+        target: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for CheckoutError {
+    fn from(message: String) -> Self {
+        CheckoutError::Failed { message }
+    }
+}
+
+/// List local and remote branches with tracking and merge information.
+#[tauri::command]
+pub async fn git_branch_list(workspace_path: String) -> Result<Vec<GitBranch>, String> {
+    let output = run_git(
+        &workspace_path,
+        &["for-each-ref", BRANCH_FORMAT, "refs/heads", "refs/remotes"],
+    )
+    .await?;
+    let mut branches = parse_branches(&output);
+
+    if let Some(default) = default_branch(&workspace_path).await {
+        let merged = run_git(
+            &workspace_path,
+            &[
+                "for-each-ref",
+                "--format=%(refname:short)",
+                "--merged",
+                &default,
+                "refs/heads",
+                "refs/remotes",
+            ],
+        )
+        .await?;
+        let merged: HashSet<&str> = merged.lines().collect();
+        for branch in &mut branches {
+            branch.merged = Some(merged.contains(branch.name.as_str()));
+        }
+    }
+
+    Ok(branches)
+}
+
+/// Create a branch at `start_point`, or at HEAD when omitted.
+#[tauri::command]
+pub async fn git_branch_create(
+    workspace_path: String,
+    name: String,
+    start_point: Option<String>,
+) -> Result<(), BranchError> {
+    validate_name(&name)?;
+    let mut args = vec!["branch", name.as_str()];
+    if let Some(start) = &start_point {
+        validate_name(start)?;
+        args.push(start);
+    }
+    run_branch(&workspace_path, &args, &name).await
+}
+
+/// Rename a local branch.
+#[tauri::command]
+pub async fn git_branch_rename(
+    workspace_path: String,
+    old_name: String,
+    new_name: String,
+) -> Result<(), BranchError> {
+    validate_name(&old_name)?;
+    validate_name(&new_name)?;
+    run_branch(
+        &workspace_path,
+        &["branch", "-m", &old_name, &new_name],
+        &new_name,
+    )
+    .await
+}
+
+/// Delete a local branch.
+///
+/// Branches with commits not merged into their upstream (or HEAD) are kept
+/// unless `force` is set.
+#[tauri::command]
+pub async fn git_branch_delete(
+    workspace_path: String,
+    name: String,
+    force: Option<bool>,
+) -> Result<(), BranchError> {
+    validate_name(&name)?;
+    let flag = if force.unwrap_or(false) { "-D" } else { "-d" };
+    run_branch(&workspace_path, &["branch", flag, &name], &name).await
+}
+
+/// Set the upstream of a local branch, or remove it when `upstream` is `None`.
+#[tauri::command]
+pub async fn git_branch_set_upstream(
+    workspace_path: String,
+    name: String,
+    upstream: Option<String>,
+) -> Result<(), BranchError> {
+    validate_name(&name)?;
+    match upstream {
+        Some(upstream) => {
+            let flag = format!("--set-upstream-to={}", upstream);
+            run_branch(&workspace_path, &["branch", &flag, &name], &name).await
+        }
+        None => {
+            run_branch(
+                &workspace_path,
+                &["branch", "--unset-upstream", &name],
+                &name,
+            )
+            .await
+        }
+    }
+}
+
+/// Check out a branch, remote branch or commit.
+///
+/// A remote branch such as `origin/feature` checks out the local branch of
+/// the same name, creating it to track the remote one if needed. Local
+/// changes that the checkout would overwrite are refused up front, or
+/// stashed and re-applied afterwards when `auto_stash` is set.
+#[tauri::command]
+pub async fn git_checkout(
+    workspace_path: String,
+    branch: String,
+    auto_stash: Option<bool>,
+) -> Result<CheckoutResult, CheckoutError> {
+    if branch.is_empty() || branch.starts_with('-') {
+        return Err(CheckoutError::NotFound { target: branch });
+    }
+    let path = workspace_path.as_str();
+    let target = resolve_checkout_target(path, &branch).await?;
+    let target_rev = match &target {
+        CheckoutTarget::Local(name) | CheckoutTarget::Detached(name) => name.clone(),
+        CheckoutTarget::Track { remote_branch, .. } => remote_branch.clone(),
+    };
+
+    let conflicts = overwritten_paths(path, &target_rev).await?;
+    let stash = !conflicts.is_empty();
+    if stash && !auto_stash.unwrap_or(false) {
+        return Err(CheckoutError::LocalChanges { paths: conflicts });
+    }
+    if stash {
+        let message = format!("ditloop: auto-stash before checkout of {}", branch);
+        run_git(
+            path,
+            &["stash", "push", "--include-untracked", "-m", &message],
+        )
+        .await?;
+    }
+
+    let switched = match &target {
+        CheckoutTarget::Local(name) => run_git(path, &["checkout", name]).await,
+        CheckoutTarget::Detached(rev) => run_git(path, &["checkout", "--detach", rev]).await,
+        CheckoutTarget::Track {
+            local,
+            remote_branch,
+        } => run_git(path, &["checkout", "-b", local, "--track", remote_branch]).await,
+    };
+    if let Err(mut message) = switched {
+        // Put the changes back where they were before giving up, keeping
+        // the checkout error as the one reported.
+        if stash && run_git(path, &["stash", "pop"]).await.is_err() {
+            message.push_str("\nLocal changes could not be restored; they are kept in the stash");
+        }
+        return Err(CheckoutError::Failed { message });
+    }
+
+    let auto_stash = if stash {
+        Some(reapply_stash(path).await?)
+    } else {
+        None
+    };
+
+    let (branch, created_from) = match target {
+        CheckoutTarget::Local(name) => (Some(name), None),
+        CheckoutTarget::Detached(_) => (None, None),
+        CheckoutTarget::Track {
+            local,
+            remote_branch,
+        } => (Some(local), Some(remote_branch)),
+    };
+    Ok(CheckoutResult {
+        branch,
+        created_from,
+        auto_stash,
+    })
+}
+
+/// Where a checkout request leads.
+enum CheckoutTarget {
+    Local(String),
+    /// Create `local` tracking `remote_branch`.
+    Track {
+        local: String,
+        remote_branch: String,
+    },
+    Detached(String),
+}
+
+async fn resolve_checkout_target(
+    workspace_path: &str,
+    name: &str,
+) -> Result<CheckoutTarget, CheckoutError> {
+    let exists = |full: String| async move {
+        run_git(workspace_path, &["show-ref", "--verify", "--quiet", &full])
+            .await
+            .is_ok()
+    };
+
+    if exists(format!("refs/heads/{}", name)).await {
+        return Ok(CheckoutTarget::Local(name.to_string()));
+    }
+
+    if exists(format!("refs/remotes/{}", name)).await {
+        let remotes = run_git(workspace_path, &["remote"]).await?;
+        // Remote names may contain slashes, so prefer the longest match.
+        let local = remotes
+            .lines()
+            .filter_map(|remote| name.strip_prefix(remote)?.strip_prefix('/'))
+            .min_by_key(|rest| rest.len())
+            .unwrap_or(name)
+            .to_string();
+        if exists(format!("refs/heads/{}", local)).await {
+            return Ok(CheckoutTarget::Local(local));
+        }
+        return Ok(CheckoutTarget::Track {
+            local,
+            remote_branch: name.to_string(),
+        });
+    }
+
+    let commit = format!("{}^{{commit}}", name);
+    if run_git(
+        workspace_path,
+        &["rev-parse", "--verify", "--quiet", &commit],
+    )
+    .await
+    .is_ok()
+    {
+        return Ok(CheckoutTarget::Detached(name.to_string()));
+    }
+
+    Err(CheckoutError::NotFound {
+        target: name.to_string(),
+    })
+}
+
+/// Locally changed or untracked paths that differ between HEAD and `target`,
+/// i.e. the paths a checkout would refuse to overwrite.
+async fn overwritten_paths(workspace_path: &str, target: &str) -> Result<Vec<String>, String> {
+    let list = |args: &'static [&'static str]| async move {
+        run_git(workspace_path, args).await.map(|out| {
+            out.split('\0')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // Without a commit there is nothing to compare against.
+    if run_git(
+        workspace_path,
+        &["rev-parse", "--verify", "--quiet", "HEAD"],
+    )
+    .await
+    .is_err()
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut local: HashSet<String> = list(&["diff", "--name-only", "-z", "HEAD"])
+        .await?
+        .into_iter()
+        .collect();
+    local.extend(list(&["ls-files", "-z", "--others", "--exclude-standard"]).await?);
+    if local.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let changed = run_git(
+        workspace_path,
+        &["diff", "--name-only", "-z", "HEAD", target, "--"],
+    )
+    .await?;
+    let mut paths: Vec<String> = changed
+        .split('\0')
+        .filter(|p| local.contains(*p))
+        .map(str::to_string)
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Pop the auto-stash, reporting conflicts instead of failing.
+async fn reapply_stash(workspace_path: &str) -> Result<AutoStashOutcome, String> {
+    if run_git(workspace_path, &["stash", "pop"]).await.is_ok() {
+        return Ok(AutoStashOutcome::Reapplied);
+    }
+
+    let conflicted = run_git(
+        workspace_path,
+        &["diff", "--name-only", "-z", "--diff-filter=U"],
+    )
+    .await?;
+    let paths: Vec<String> = conflicted
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+    if paths.is_empty() {
+        return Err("Failed to re-apply auto-stashed changes; they are kept in the stash".into());
+    }
+    Ok(AutoStashOutcome::Conflicted { paths })
+}
+
+/// Reject names git would read as options.
+fn validate_name(name: &str) -> Result<(), BranchError> {
+    if name.is_empty() || name.starts_with('-') {
+        return Err(BranchError::InvalidName {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Run a `git branch` command and map known failures to [`BranchError`].
+async fn run_branch(workspace_path: &str, args: &[&str], branch: &str) -> Result<(), BranchError> {
+    run_git(workspace_path, args)
+        .await
+        .map(|_| ())
+        .map_err(|message| classify_branch_error(message, branch))
+}
+
+fn classify_branch_error(message: String, branch: &str) -> BranchError {
+    let branch = branch.to_string();
+    if message.contains("is not fully merged") {
+        BranchError::Unmerged { branch }
+    } else if message.contains("Cannot delete branch") || message.contains("checked out at") {
+        BranchError::CheckedOut { branch }
+    } else if message.contains("already exists") {
+        BranchError::AlreadyExists { branch }
+    } else if message.contains("is not a valid branch name") {
+        BranchError::InvalidName { name: branch }
+    } else {
+        BranchError::Failed { message }
+    }
+}
+
+/// The branch other branches are compared against: the remote HEAD of
+/// `origin` if known, otherwise a local `main` or `master`.
+pub(crate) async fn default_branch(workspace_path: &str) -> Option<String> {
+    if let Ok(output) = run_git(
+        workspace_path,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    )
+    .await
+    {
+        return Some(output.trim().to_string());
+    }
+    for name in ["main", "master"] {
+        let full = format!("refs/heads/{}", name);
+        if run_git(workspace_path, &["show-ref", "--verify", "--quiet", &full])
+            .await
+            .is_ok()
+        {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Parse `git for-each-ref` output in [`BRANCH_FORMAT`].
+///
+/// Symbolic refs such as `origin/HEAD` are skipped.
+pub fn parse_branches(output: &str) -> Vec<GitBranch> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\0').collect();
+            if fields.len() != BRANCH_FIELDS || !fields[2].is_empty() {
+                return None;
+            }
+            let (name, is_remote) = match fields[0].strip_prefix("refs/heads/") {
+                Some(name) => (name, false),
+                None => (fields[0].strip_prefix("refs/remotes/")?, true),
+            };
+            let (ahead, behind, upstream_gone) = parse_track(fields[4]);
+            Some(GitBranch {
+                name: name.to_string(),
+                is_current: fields[1] == "*",
+                is_remote,
+                upstream: Some(fields[3].to_string()).filter(|s| !s.is_empty()),
+                upstream_gone,
+                ahead,
+                behind,
+                last_commit_date: fields[5].to_string(),
+                last_commit_author: fields[6].to_string(),
+                last_commit_hash: fields[7].to_string(),
+                merged: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse `%(upstream:track,nobracket)`, e.g. `ahead 1, behind 2` or `gone`.
+fn parse_track(track: &str) -> (u32, u32, bool) {
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in track.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            behind = n.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind, track == "gone")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, read, repo_path, write};
+
+    fn commit(dir: &tempfile::TempDir, message: &str) {
+        git(
+            dir.path(),
+            &["commit", "-q", "--allow-empty", "-m", message],
+        );
+    }
+
+    #[test]
+    fn test_parse_track() {
+        assert_eq!(parse_track(""), (0, 0, false));
+        assert_eq!(parse_track("ahead 2"), (2, 0, false));
+        assert_eq!(parse_track("ahead 1, behind 3"), (1, 3, false));
+        assert_eq!(parse_track("gone"), (0, 0, true));
+    }
+
+    #[tokio::test]
+    async fn test_branch_list_tracking_and_merged() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let remote = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init", "-q", "--bare", "-b", "main"]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", &remote.path().to_string_lossy()],
+        );
+        git(dir.path(), &["push", "-q", "-u", "origin", "main"]);
+        git(dir.path(), &["remote", "set-head", "origin", "main"]);
+        commit(&dir, "local only");
+
+        git(dir.path(), &["branch", "merged", "origin/main"]);
+        git(dir.path(), &["checkout", "-q", "-b", "feature"]);
+        commit(&dir, "feature work");
+        git(dir.path(), &["checkout", "-q", "main"]);
+
+        let branches = git_branch_list(repo_path(&dir)).await.unwrap();
+        let find = |name: &str| branches.iter().find(|b| b.name == name).unwrap();
+
+        let main = find("main");
+        assert!(main.is_current);
+        assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((main.ahead, main.behind), (1, 0));
+        assert_eq!(main.last_commit_author, "Test User");
+        assert_eq!(main.merged, Some(false));
+
+        assert_eq!(find("merged").merged, Some(true));
+        assert_eq!(find("feature").merged, Some(false));
+        assert!(find("origin/main").is_remote);
+        assert!(!branches.iter().any(|b| b.name == "origin/HEAD"));
+    }
+
+    #[tokio::test]
+    async fn test_create_rename_and_set_upstream() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+        commit(&dir, "second");
+
+        git_branch_create(path.clone(), "old".into(), Some("HEAD~1".into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            git_branch_create(path.clone(), "old".into(), None).await,
+            Err(BranchError::AlreadyExists {
+                branch: "old".to_string()
+            })
+        );
+        assert_eq!(
+            git_branch_create(path.clone(), "--force".into(), None).await,
+            Err(BranchError::InvalidName {
+                name: "--force".to_string()
+            })
+        );
+
+        git_branch_rename(path.clone(), "old".into(), "new".into())
+            .await
+            .unwrap();
+        let head = git(dir.path(), &["rev-parse", "new"]);
+        let first = git(dir.path(), &["rev-parse", "HEAD~1"]);
+        assert_eq!(head, first);
+
+        git_branch_set_upstream(path.clone(), "new".into(), Some("main".into()))
+            .await
+            .unwrap();
+        let upstream = git(dir.path(), &["rev-parse", "--abbrev-ref", "new@{u}"]);
+        assert_eq!(upstream.trim(), "main");
+        git_branch_set_upstream(path, "new".into(), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_protects_unmerged_branches() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+        git(dir.path(), &["checkout", "-q", "-b", "feature"]);
+        commit(&dir, "feature work");
+        git(dir.path(), &["checkout", "-q", "main"]);
+
+        assert_eq!(
+            git_branch_delete(path.clone(), "feature".into(), None).await,
+            Err(BranchError::Unmerged {
+                branch: "feature".to_string()
+            })
+        );
+        assert_eq!(
+            git_branch_delete(path.clone(), "main".into(), Some(true)).await,
+            Err(BranchError::CheckedOut {
+                branch: "main".to_string()
+            })
+        );
+        git_branch_delete(path, "feature".into(), Some(true))
+            .await
+            .unwrap();
+        assert!(!git(dir.path(), &["branch"]).contains("feature"));
+    }
+
+    /// `f.txt` with ten lines, and a `target` branch changing its first line.
+    fn repo_with_target_branch() -> tempfile::TempDir {
+        let lines: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+        let dir = init_repo(&[("f.txt", &lines)]);
+        git(dir.path(), &["checkout", "-q", "-b", "target"]);
+        write(&dir, "f.txt", &lines.replacen("line 1\n", "target 1\n", 1));
+        git(dir.path(), &["commit", "-q", "-am", "target change"]);
+        git(dir.path(), &["checkout", "-q", "main"]);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_checkout_carries_unrelated_changes() {
+        let dir = repo_with_target_branch();
+        write(&dir, "other.txt", "new\n");
+
+        let result = git_checkout(repo_path(&dir), "target".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            CheckoutResult {
+                branch: Some("target".to_string()),
+                created_from: None,
+                auto_stash: None,
+            }
+        );
+        assert_eq!(read(&dir, "other.txt"), "new\n");
+    }
+
+    #[tokio::test]
+    async fn test_checkout_refuses_conflicting_changes() {
+        let dir = repo_with_target_branch();
+        let edited = read(&dir, "f.txt").replace("line 10", "local 10");
+        write(&dir, "f.txt", &edited);
+
+        let err = git_checkout(repo_path(&dir), "target".into(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            CheckoutError::LocalChanges {
+                paths: vec!["f.txt".to_string()]
+            }
+        );
+        let head = git(dir.path(), &["rev-parse", "--abbrev-ref", "HEAD"]);
+        assert_eq!(head.trim(), "main");
+        assert_eq!(read(&dir, "f.txt"), edited);
+    }
+
+    #[tokio::test]
+    async fn test_checkout_auto_stash_reapplies_changes() {
+        let dir = repo_with_target_branch();
+        let edited = read(&dir, "f.txt").replace("line 10", "local 10");
+        write(&dir, "f.txt", &edited);
+
+        let result = git_checkout(repo_path(&dir), "target".into(), Some(true))
+            .await
+            .unwrap();
+        assert_eq!(result.auto_stash, Some(AutoStashOutcome::Reapplied));
+        let content = read(&dir, "f.txt");
+        assert!(content.starts_with("target 1\n"));
+        assert!(content.contains("local 10"));
+        assert!(git(dir.path(), &["stash", "list"]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_checkout_auto_stash_reports_conflicts() {
+        let dir = repo_with_target_branch();
+        let edited = read(&dir, "f.txt").replace("line 1\n", "local 1\n");
+        write(&dir, "f.txt", &edited);
+
+        let result = git_checkout(repo_path(&dir), "target".into(), Some(true))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.auto_stash,
+            Some(AutoStashOutcome::Conflicted {
+                paths: vec!["f.txt".to_string()]
+            })
+        );
+        assert!(!git(dir.path(), &["stash", "list"]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_checkout_remote_branch_creates_tracking_branch() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let remote = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init", "-q", "--bare", "-b", "main"]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", &remote.path().to_string_lossy()],
+        );
+        git(
+            dir.path(),
+            &["push", "-q", "origin", "main", "main:feature"],
+        );
+        git(dir.path(), &["fetch", "-q", "origin"]);
+
+        let result = git_checkout(repo_path(&dir), "origin/feature".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.branch.as_deref(), Some("feature"));
+        assert_eq!(result.created_from.as_deref(), Some("origin/feature"));
+        let upstream = git(dir.path(), &["rev-parse", "--abbrev-ref", "@{u}"]);
+        assert_eq!(upstream.trim(), "origin/feature");
+
+        // A second checkout reuses the local branch.
+        git(dir.path(), &["checkout", "-q", "main"]);
+        let result = git_checkout(repo_path(&dir), "origin/feature".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.created_from, None);
+
+        let err = git_checkout(repo_path(&dir), "missing".into(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            CheckoutError::NotFound {
+                target: "missing".to_string()
+            }
+        );
+    }
+}
@@ -1,9 +1,17 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
 use tokio::process::Command;
 
+use super::config_state::ConfigState;
+use super::config_validate::ConfigDiagnostic;
+use super::git::run_git;
+use super::ssh::{setup_profile_ssh, SshSetupResult};
+
 /// Profile from DitLoop config.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfileConfig {
     pub name: String,
@@ -14,110 +22,437 @@ pub struct ProfileConfig {
     pub ssh_key: Option<String>,
     #[serde(default)]
     pub platform: Option<String>,
+    /// How commits made with this profile are signed.
+    #[serde(default)]
+    pub signing: Option<SigningConfig>,
+}
+
+/// Commit signing settings for a profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum SigningConfig {
+    /// OpenPGP signing with a GPG key id.
+    Gpg {
+        key: String,
+        /// Overrides `gpg.program`.
+        #[serde(default)]
+        program: Option<String>,
+    },
+    /// SSH signing with a key file, e.g. `~/.ssh/id_ed25519.pub`.
+    Ssh {
+        key: String,
+        /// Overrides `gpg.ssh.program`.
+        #[serde(default)]
+        program: Option<String>,
+    },
+    /// Never sign, even if the repository enables `commit.gpgsign`.
+    None,
 }
 
 /// Workspace entry from DitLoop config.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceConfig {
     pub name: String,
     pub path: String,
     #[serde(default = "default_workspace_type")]
     pub r#type: String,
+    /// Profile key, empty when the workspace has none.
+    #[serde(default)]
     pub profile: String,
     #[serde(default)]
     pub aidf: bool,
+    /// For a group, patterns a repository must match to be included; all
+    /// repositories under the path when empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// For a group, patterns of directories to leave out.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Per-workspace settings; a repository can set them in its own
+    /// `.ditloop.yml` too, the commands only when it is trusted.
+    #[serde(flatten)]
+    pub settings: WorkspaceSettings,
+}
+
+/// Per-workspace settings, set in the user's config. A repository can
+/// commit `emailDomain` and `protectedBranches`; the commands run on its
+/// behalf only count once it is listed in `trustedRepos`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSettings {
+    /// Domain commit emails are expected to use, e.g. `company.com`.
+    #[serde(default)]
+    pub email_domain: Option<String>,
+    /// Command of the preferred AI CLI tool.
+    #[serde(default)]
+    pub ai_tool: Option<String>,
+    /// Arguments the AI tool is launched with by default.
+    #[serde(default)]
+    pub ai_args: Option<Vec<String>>,
+    /// Branches that shouldn't be committed or force-pushed to directly.
+    #[serde(default)]
+    pub protected_branches: Option<Vec<String>>,
+    /// Editor command used to open the workspace.
+    #[serde(default)]
+    pub editor: Option<String>,
+}
+
+impl WorkspaceConfig {
+    /// Whether the entry is a directory of repositories rather than one.
+    pub fn is_group(&self) -> bool {
+        self.r#type == "group"
+    }
 }
 
 fn default_workspace_type() -> String {
     "single".to_string()
 }
 
+/// A profile in the list layout.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedProfile {
+    name: String,
+    git_user: Option<String>,
+    email: String,
+    #[serde(default)]
+    ssh_host: Option<String>,
+    #[serde(default)]
+    ssh_key: Option<String>,
+    #[serde(default)]
+    platform: Option<String>,
+    #[serde(default)]
+    signing: Option<SigningConfig>,
+}
+
+fn deserialize_profiles<'de, D>(deserializer: D) -> Result<HashMap<String, ProfileConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::Null => Ok(HashMap::new()),
+        value @ serde_yaml::Value::Sequence(_) => {
+            let listed: Vec<ListedProfile> =
+                serde_yaml::from_value(value).map_err(D::Error::custom)?;
+            let mut profiles = HashMap::new();
+            for (index, p) in listed.into_iter().enumerate() {
+                if profiles.contains_key(&p.name) {
+                    return Err(D::Error::custom(duplicate_profile_error(index, &p.name)));
+                }
+                let profile = ProfileConfig {
+                    name: p.git_user.unwrap_or_else(|| p.name.clone()),
+                    email: p.email,
+                    ssh_host: p.ssh_host,
+                    ssh_key: p.ssh_key,
+                    platform: p.platform,
+                    signing: p.signing,
+                };
+                profiles.insert(p.name, profile);
+            }
+            Ok(profiles)
+        }
+        value => serde_yaml::from_value(value).map_err(D::Error::custom),
+    }
+}
+
+/// Error for a list-layout profile whose `name` an earlier entry already
+/// used; keying by name would silently drop one of them.
+pub(crate) fn duplicate_profile_error(index: usize, name: &str) -> String {
+    format!("profiles[{}]: duplicate profile name '{}'", index, name)
+}
+
 /// Full DitLoop config file.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DitLoopConfigFile {
-    #[serde(default)]
-    pub profiles: std::collections::HashMap<String, ProfileConfig>,
+    /// Layout version; absent in files written before versioning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// Profiles by key. Also read from the older list layout, where each
+    /// entry has a `name` key and the git user name in `gitUser`.
+    #[serde(default, deserialize_with = "deserialize_profiles")]
+    pub profiles: HashMap<String, ProfileConfig>,
     #[serde(default)]
     pub workspaces: Vec<WorkspaceConfig>,
+    /// Repositories, or directories of them, whose settings files may also
+    /// set the editor and AI tool.
+    #[serde(
+        rename = "trustedRepos",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub trusted_repos: Vec<String>,
 }
 
 /// Config load result with metadata.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigLoadResult {
     pub config: DitLoopConfigFile,
     pub config_path: String,
     pub exists: bool,
+    /// Problems found in the file; a file that fails to parse loads as the
+    /// default config with the parse error here.
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    /// The file uses an older layout; `migrate_ditloop_config` rewrites it.
+    pub needs_migration: bool,
+}
+
+/// Load the DitLoop config file.
+///
+/// Served from the cached config, which is re-read when the file changes.
+#[tauri::command]
+pub fn load_ditloop_config(
+    config_state: State<'_, ConfigState>,
+) -> Result<ConfigLoadResult, String> {
+    config_state.load()
+}
+
+/// Where a profile switch writes the git identity.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileScope {
+    /// The user's global git config, affecting every repository.
+    Global,
+    /// The workspace repository's own `.git/config`.
+    Local,
+    /// An `includeIf "gitdir:"` block covering every repository under the
+    /// workspace directory.
+    IncludeIf,
 }
 
-/// Load DitLoop config from ~/.ditloop/config.yml.
+/// Switch to a named git profile from DitLoop config.
+///
+/// Without a workspace the identity is written to the global git config.
+/// With one it defaults to the repository's local config; `include_if`
+/// instead points a conditional include for the whole directory tree at a
+/// generated `~/.ditloop/profiles/<profile>.gitconfig`.
+///
+/// Applying a profile to a single repository also applies its SSH host alias
+/// and key, returning what was changed.
 #[tauri::command]
-pub fn load_ditloop_config() -> Result<ConfigLoadResult, String> {
+pub async fn switch_git_profile(
+    config_state: State<'_, ConfigState>,
+    profile_name: String,
+    workspace_path: Option<String>,
+    scope: Option<ProfileScope>,
+    load_ssh_agent: Option<bool>,
+) -> Result<Option<SshSetupResult>, String> {
+    let config = config_state.config()?.ok_or("Config file not found")?;
+
+    let profile = config
+        .profiles
+        .get(&profile_name)
+        .ok_or_else(|| format!("Profile '{}' not found in config", profile_name))?;
+
     let home = dirs::home_dir().ok_or("Could not determine home directory")?;
-    let config_path = home.join(".ditloop").join("config.yml");
-    let config_path_str = config_path.to_string_lossy().to_string();
+    let target = GitConfigTarget {
+        global: vec!["--global".to_string()],
+        profiles_dir: home.join(".ditloop").join("profiles"),
+        cwd: home.to_string_lossy().to_string(),
+    };
+    let workspace_path = workspace_path.map(|path| expand_home(&path));
+    let scope = scope.unwrap_or(if workspace_path.is_some() {
+        ProfileScope::Local
+    } else {
+        ProfileScope::Global
+    });
 
-    if !config_path.exists() {
-        return Ok(ConfigLoadResult {
-            config: DitLoopConfigFile::default(),
-            config_path: config_path_str,
-            exists: false,
-        });
+    apply_profile(
+        &profile_name,
+        profile,
+        scope,
+        workspace_path.as_deref(),
+        &target,
+    )
+    .await?;
+
+    match (scope, workspace_path) {
+        (ProfileScope::Local, Some(path)) => {
+            let load_agent = load_ssh_agent.unwrap_or(false);
+            setup_profile_ssh(&path, profile, load_agent, None)
+                .await
+                .map(Some)
+        }
+        _ => Ok(None),
     }
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
+/// Where non-local profile writes go.
+pub(crate) struct GitConfigTarget {
+    /// Location flags for the global config, e.g. `--global`.
+    pub global: Vec<String>,
+    /// Directory holding generated per-profile include files.
+    pub profiles_dir: PathBuf,
+    /// Directory git runs in for global writes.
+    pub cwd: String,
+}
 
-    let config: DitLoopConfigFile = serde_yaml::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+/// Write a profile's identity at the requested scope.
+pub(crate) async fn apply_profile(
+    profile_name: &str,
+    profile: &ProfileConfig,
+    scope: ProfileScope,
+    workspace_path: Option<&str>,
+    target: &GitConfigTarget,
+) -> Result<(), String> {
+    match scope {
+        ProfileScope::Global => {
+            let global: Vec<&str> = target.global.iter().map(String::as_str).collect();
+            write_identity(&target.cwd, &global, profile).await
+        }
+        ProfileScope::Local => {
+            let path = workspace_path.ok_or("A workspace path is required for local scope")?;
+            write_identity(path, &["--local"], profile).await
+        }
+        ProfileScope::IncludeIf => {
+            let path = workspace_path.ok_or("A workspace path is required for includeIf scope")?;
+            // The name becomes a file name; it must not reach outside the directory.
+            let mut components = Path::new(profile_name).components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(std::path::Component::Normal(_)), None)
+            ) {
+                return Err(format!(
+                    "Profile name '{}' cannot be used as a file name",
+                    profile_name
+                ));
+            }
+            fs::create_dir_all(&target.profiles_dir)
+                .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+            let include_file = target
+                .profiles_dir
+                .join(format!("{}.gitconfig", profile_name));
+            let include_str = include_file.to_string_lossy().to_string();
+            write_identity(&target.cwd, &["--file", &include_str], profile).await?;
 
-    // Expand ~ to home directory in workspace paths
-    let mut config = config;
-    let home_str = home.to_string_lossy().to_string();
-    for ws in &mut config.workspaces {
-        if ws.path.starts_with('~') {
-            ws.path = ws.path.replacen('~', &home_str, 1);
+            // A trailing slash makes git match every repository below the directory.
+            let dir = path.trim_end_matches('/');
+            let key = format!("includeIf.gitdir:{}/.path", dir);
+            let mut args = vec!["config"];
+            args.extend(target.global.iter().map(String::as_str));
+            args.extend([key.as_str(), include_str.as_str()]);
+            run_git(&target.cwd, &args).await.map(|_| ())
         }
     }
+}
 
-    Ok(ConfigLoadResult {
-        config,
-        config_path: config_path_str,
-        exists: true,
-    })
+/// Set user.name and user.email in the config file selected by `location`.
+async fn write_identity(
+    cwd: &str,
+    location: &[&str],
+    profile: &ProfileConfig,
+) -> Result<(), String> {
+    for (key, value) in [("user.name", &profile.name), ("user.email", &profile.email)] {
+        let mut args = vec!["config"];
+        args.extend_from_slice(location);
+        args.extend([key, value.as_str()]);
+        run_git(cwd, &args)
+            .await
+            .map_err(|e| format!("Failed to set git {}: {}", key, e))?;
+    }
+    Ok(())
 }
 
-/// Switch to a named git profile from DitLoop config.
-/// Sets global git user.name and user.email.
-#[tauri::command]
-pub async fn switch_git_profile(profile_name: String) -> Result<(), String> {
+/// Expand a leading `~` to the home directory.
+pub(crate) fn expand_home(path: &str) -> String {
+    match dirs::home_dir() {
+        Some(home) if path.starts_with('~') => path.replacen('~', &home.to_string_lossy(), 1),
+        _ => path.to_string(),
+    }
+}
+
+/// Path of the user's DitLoop config file.
+///
+/// `DITLOOP_CONFIG` wins when set. Otherwise the first existing file of
+/// `~/.ditloop/config.yml`, `~/.ditloop/config.yaml`,
+/// `$XDG_CONFIG_HOME/ditloop/config.yml` and `.../config.yaml` is used, and
+/// `~/.ditloop/config.yml` when none exists yet.
+pub(crate) fn config_file_path() -> Result<PathBuf, String> {
+    Ok(first_existing(config_file_candidates()?))
+}
+
+/// Every location [`config_file_path`] considers, in order of precedence.
+/// `DITLOOP_CONFIG`, when set, is the only one.
+pub(crate) fn config_file_candidates() -> Result<Vec<PathBuf>, String> {
+    if let Some(path) = std::env::var_os("DITLOOP_CONFIG").filter(|p| !p.is_empty()) {
+        return Ok(vec![PathBuf::from(expand_home(&path.to_string_lossy()))]);
+    }
     let home = dirs::home_dir().ok_or("Could not determine home directory")?;
-    let config_path = home.join(".ditloop").join("config.yml");
+    let xdg = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from);
+    Ok(candidate_paths(&home, xdg.as_deref()))
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-    let config: DitLoopConfigFile = serde_yaml::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+fn candidate_paths(home: &Path, xdg_config_home: Option<&Path>) -> Vec<PathBuf> {
+    let ditloop_dir = home.join(".ditloop");
+    let xdg_dir = xdg_config_home
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| home.join(".config"))
+        .join("ditloop");
+    [&ditloop_dir, &xdg_dir]
+        .iter()
+        .flat_map(|dir| [dir.join("config.yml"), dir.join("config.yaml")])
+        .collect()
+}
 
-    let profile = config
-        .profiles
-        .get(&profile_name)
-        .ok_or_else(|| format!("Profile '{}' not found in config", profile_name))?;
+/// The first candidate that exists, or the first one when none does yet.
+fn first_existing(mut candidates: Vec<PathBuf>) -> PathBuf {
+    let index = candidates
+        .iter()
+        .position(|path| path.is_file())
+        .unwrap_or(0);
+    candidates.swap_remove(index)
+}
 
-    Command::new("git")
-        .args(["config", "--global", "user.name", &profile.name])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to set git user.name: {}", e))?;
+/// Read and parse a config file, expanding `~` in workspace and trusted
+/// repository paths.
+/// Returns `None` when the file does not exist.
+pub(crate) fn read_config_file(config_path: &Path) -> Result<Option<DitLoopConfigFile>, String> {
+    if !config_path.exists() {
+        return Ok(None);
+    }
 
-    Command::new("git")
-        .args(["config", "--global", "user.email", &profile.email])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to set git user.email: {}", e))?;
+    let content =
+        fs::read_to_string(config_path).map_err(|e| format!("Failed to read config: {}", e))?;
 
-    Ok(())
+    let mut config: DitLoopConfigFile =
+        serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    // Expand ~ to home directory in workspace paths
+    for ws in &mut config.workspaces {
+        ws.path = expand_home(&ws.path);
+    }
+    for path in &mut config.trusted_repos {
+        *path = expand_home(path);
+    }
+
+    Ok(Some(config))
+}
+
+/// Find the configured workspace containing `path`.
+///
+/// The deepest matching workspace wins, so a repository inside a configured
+/// directory resolves to that entry.
+pub fn find_workspace_config<'a>(
+    config: &'a DitLoopConfigFile,
+    path: &Path,
+) -> Option<&'a WorkspaceConfig> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    config
+        .workspaces
+        .iter()
+        .filter_map(|ws| {
+            let ws_path = PathBuf::from(&ws.path);
+            let ws_path = fs::canonicalize(&ws_path).unwrap_or(ws_path);
+            path.starts_with(&ws_path)
+                .then(|| (ws_path.components().count(), ws))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, ws)| ws)
 }
 
 /// Get the current git identity (user.email).
@@ -140,6 +475,7 @@ pub async fn get_git_identity() -> Result<Option<String>, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path};
 
     #[test]
     fn test_parse_config_yaml() {
@@ -182,6 +518,129 @@ workspaces:
         assert_eq!(ws2.r#type, "single"); // default
     }
 
+    #[test]
+    fn test_parse_list_profiles() {
+        let yaml = r#"
+workspaces:
+  - name: my-project
+    path: ~/projects/my-project
+profiles:
+  - name: personal
+    email: you@example.com
+    gitUser: Your Name
+    sshKey: ~/.ssh/id_ed25519
+"#;
+        let config: DitLoopConfigFile = serde_yaml::from_str(yaml).unwrap();
+        let personal = &config.profiles["personal"];
+        assert_eq!(personal.name, "Your Name");
+        assert_eq!(personal.email, "you@example.com");
+        assert_eq!(personal.ssh_key.as_deref(), Some("~/.ssh/id_ed25519"));
+        assert_eq!(config.workspaces[0].profile, "");
+
+        let duplicated = format!("{}  - name: personal\n    email: other@example.com\n", yaml);
+        let err = serde_yaml::from_str::<DitLoopConfigFile>(&duplicated).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("duplicate profile name 'personal'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_config_path_precedence() {
+        let home = tempfile::tempdir().unwrap();
+        let xdg = home.path().join("xdg");
+        let default = home.path().join(".ditloop").join("config.yml");
+        assert_eq!(
+            first_existing(candidate_paths(home.path(), Some(&xdg))),
+            default
+        );
+
+        let xdg_yaml = xdg.join("ditloop").join("config.yaml");
+        fs::create_dir_all(xdg_yaml.parent().unwrap()).unwrap();
+        fs::write(&xdg_yaml, "").unwrap();
+        assert_eq!(
+            first_existing(candidate_paths(home.path(), Some(&xdg))),
+            xdg_yaml
+        );
+
+        let yaml = home.path().join(".ditloop").join("config.yaml");
+        fs::create_dir_all(yaml.parent().unwrap()).unwrap();
+        fs::write(&yaml, "").unwrap();
+        assert_eq!(
+            first_existing(candidate_paths(home.path(), Some(&xdg))),
+            yaml
+        );
+
+        fs::write(&default, "").unwrap();
+        assert_eq!(
+            first_existing(candidate_paths(home.path(), Some(&xdg))),
+            default
+        );
+    }
+
+    #[test]
+    fn test_parse_group_workspace() {
+        let yaml = r#"
+workspaces:
+  - name: clients
+    path: /home/user/clients
+    type: group
+    profile: work
+    exclude: [archive, "old-*"]
+"#;
+        let config: DitLoopConfigFile = serde_yaml::from_str(yaml).unwrap();
+        let ws = &config.workspaces[0];
+        assert!(ws.is_group());
+        assert!(ws.include.is_empty());
+        assert_eq!(ws.exclude, ["archive", "old-*"]);
+    }
+
+    #[test]
+    fn test_parse_profile_signing() {
+        let yaml = r#"
+profiles:
+  work:
+    name: Work User
+    email: work@example.com
+    signing:
+      format: ssh
+      key: ~/.ssh/id_work.pub
+  oss:
+    name: Test User
+    email: test@example.com
+    signing:
+      format: gpg
+      key: 3AA5C34371567BD2
+      program: gpg2
+  scratch:
+    name: Test User
+    email: test@example.com
+    signing:
+      format: none
+"#;
+        let config: DitLoopConfigFile = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.profiles["work"].signing,
+            Some(SigningConfig::Ssh {
+                key: "~/.ssh/id_work.pub".to_string(),
+                program: None,
+            })
+        );
+        assert_eq!(
+            config.profiles["oss"].signing,
+            Some(SigningConfig::Gpg {
+                key: "3AA5C34371567BD2".to_string(),
+                program: Some("gpg2".to_string()),
+            })
+        );
+        assert_eq!(
+            config.profiles["scratch"].signing,
+            Some(SigningConfig::None)
+        );
+    }
+
     #[test]
     fn test_tilde_expansion_in_workspace_paths() {
         let yaml = r#"
@@ -218,7 +677,114 @@ workspaces:
     fn test_load_ditloop_config_returns_result() {
         // This test just verifies the function doesn't panic.
         // It may return exists: false if no config file is present.
-        let result = load_ditloop_config();
+        let result = ConfigState::default().load();
         assert!(result.is_ok());
     }
+
+    fn work_profile() -> ProfileConfig {
+        ProfileConfig {
+            name: "Work User".to_string(),
+            email: "work@corp.com".to_string(),
+            ssh_host: None,
+            ssh_key: None,
+            platform: None,
+            signing: None,
+        }
+    }
+
+    fn test_target(dir: &Path) -> GitConfigTarget {
+        let global = dir.join("global.gitconfig");
+        GitConfigTarget {
+            global: vec!["--file".to_string(), global.to_string_lossy().to_string()],
+            profiles_dir: dir.join("profiles"),
+            cwd: dir.to_string_lossy().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_profile_local_scope() {
+        let repo = init_repo(&[("a.txt", "a\n")]);
+        let home = tempfile::tempdir().unwrap();
+        apply_profile(
+            "work",
+            &work_profile(),
+            ProfileScope::Local,
+            Some(&repo_path(&repo)),
+            &test_target(home.path()),
+        )
+        .await
+        .unwrap();
+
+        let email = git(repo.path(), &["config", "--local", "user.email"]);
+        assert_eq!(email.trim(), "work@corp.com");
+        assert!(!home.path().join("global.gitconfig").exists());
+    }
+
+    #[tokio::test]
+    async fn test_switch_profile_include_if_covers_directory_tree() {
+        let home = tempfile::tempdir().unwrap();
+        let tree = tempfile::tempdir().unwrap();
+        let target = test_target(home.path());
+        apply_profile(
+            "work",
+            &work_profile(),
+            ProfileScope::IncludeIf,
+            Some(&tree.path().to_string_lossy()),
+            &target,
+        )
+        .await
+        .unwrap();
+
+        let include =
+            fs::read_to_string(home.path().join("profiles").join("work.gitconfig")).unwrap();
+        assert!(include.contains("work@corp.com"));
+
+        // Pull the generated global file into a repository below the tree.
+        let repo = tree.path().join("project");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        let global = home.path().join("global.gitconfig");
+        git(
+            &repo,
+            &["config", "include.path", &global.to_string_lossy()],
+        );
+        let email = git(&repo, &["config", "user.email"]);
+        assert_eq!(email.trim(), "work@corp.com");
+    }
+
+    #[tokio::test]
+    async fn test_switch_profile_include_if_rejects_path_names() {
+        let home = tempfile::tempdir().unwrap();
+        let tree = tempfile::tempdir().unwrap();
+        let target = test_target(home.path());
+        for name in ["../escape", "a/b", "..", ""] {
+            let err = apply_profile(
+                name,
+                &work_profile(),
+                ProfileScope::IncludeIf,
+                Some(&tree.path().to_string_lossy()),
+                &target,
+            )
+            .await
+            .unwrap_err();
+            assert!(err.contains("file name"), "{}: {}", name, err);
+        }
+        assert!(!home.path().join("escape.gitconfig").exists());
+        assert!(!home.path().join("global.gitconfig").exists());
+    }
+
+    #[tokio::test]
+    async fn test_switch_profile_local_requires_workspace() {
+        let home = tempfile::tempdir().unwrap();
+        let err = apply_profile(
+            "work",
+            &work_profile(),
+            ProfileScope::Local,
+            None,
+            &test_target(home.path()),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("workspace path"));
+    }
 }
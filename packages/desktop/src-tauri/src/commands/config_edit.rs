@@ -0,0 +1,739 @@
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::config::{
+    config_file_path, read_config_file, DitLoopConfigFile, ProfileConfig, WorkspaceConfig,
+};
+use super::config_migrate::migrate_config_text;
+use super::config_validate::{validate_config_text, ConfigDiagnostic, DiagnosticSeverity};
+
+/// Indentation for entries added to a section that has none yet.
+pub(crate) const DEFAULT_INDENT: usize = 2;
+
+/// Add a profile under `key`.
+#[tauri::command]
+pub fn add_profile(key: String, profile: ProfileConfig) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(
+        &config_file_path()?,
+        ConfigEdit::AddProfile { key, profile },
+    )
+}
+
+/// Replace the profile stored under `key`.
+#[tauri::command]
+pub fn update_profile(key: String, profile: ProfileConfig) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(
+        &config_file_path()?,
+        ConfigEdit::UpdateProfile { key, profile },
+    )
+}
+
+/// Remove the profile stored under `key`. Refused while a workspace uses it.
+#[tauri::command]
+pub fn remove_profile(key: String) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(&config_file_path()?, ConfigEdit::RemoveProfile { key })
+}
+
+/// Append a workspace entry.
+#[tauri::command]
+pub fn add_workspace(workspace: WorkspaceConfig) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(&config_file_path()?, ConfigEdit::AddWorkspace { workspace })
+}
+
+/// Replace the workspace entry called `name`; the new entry may rename it.
+#[tauri::command]
+pub fn update_workspace(
+    name: String,
+    workspace: WorkspaceConfig,
+) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(
+        &config_file_path()?,
+        ConfigEdit::UpdateWorkspace { name, workspace },
+    )
+}
+
+/// Remove the workspace entry called `name`.
+#[tauri::command]
+pub fn remove_workspace(name: String) -> Result<DitLoopConfigFile, String> {
+    edit_config_file(&config_file_path()?, ConfigEdit::RemoveWorkspace { name })
+}
+
+/// A single change to the config file.
+pub(crate) enum ConfigEdit {
+    AddProfile {
+        key: String,
+        profile: ProfileConfig,
+    },
+    UpdateProfile {
+        key: String,
+        profile: ProfileConfig,
+    },
+    RemoveProfile {
+        key: String,
+    },
+    AddWorkspace {
+        workspace: WorkspaceConfig,
+    },
+    UpdateWorkspace {
+        name: String,
+        workspace: WorkspaceConfig,
+    },
+    RemoveWorkspace {
+        name: String,
+    },
+}
+
+/// Where an edit lands in the file text, with entries rendered at column zero.
+enum Splice {
+    Insert {
+        section: &'static str,
+        entry: String,
+    },
+    Replace {
+        section: &'static str,
+        index: usize,
+        entry: String,
+    },
+    Remove {
+        section: &'static str,
+        index: usize,
+    },
+}
+
+/// Apply one edit to the config file at `path` and return the new config.
+///
+/// Only the edited entry is rewritten, so comments and formatting elsewhere
+/// survive; if the file's layout can't be edited in place the whole file is
+/// re-serialized, still in its original key order. The edit is refused if
+/// it introduces a problem the config did not already have. A file in an
+/// older layout is migrated as part of the edit.
+pub(crate) fn edit_config_file(path: &Path, edit: ConfigEdit) -> Result<DitLoopConfigFile, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read config: {}", e)),
+    };
+    let text = migrate_config_text(&text)?.unwrap_or(text);
+    let mut doc: Value =
+        serde_yaml::from_str(&text).map_err(|e| format!("Failed to parse config: {}", e))?;
+    if doc.is_null() {
+        doc = Value::Mapping(Mapping::new());
+    }
+    let before: DitLoopConfigFile = serde_yaml::from_value(doc.clone())
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    let splice = apply_edit(&mut doc, &before, edit)?;
+    let output = match splice_text(&text, &splice)
+        .filter(|out| serde_yaml::from_str::<Value>(out).ok().as_ref() == Some(&doc))
+    {
+        Some(output) => output,
+        None => {
+            serde_yaml::to_string(&doc).map_err(|e| format!("Failed to serialize config: {}", e))?
+        }
+    };
+
+    let existing = errors(&validate_config_text(&text));
+    let introduced: Vec<String> = errors(&validate_config_text(&output))
+        .into_iter()
+        .filter(|message| !existing.contains(message))
+        .collect();
+    if !introduced.is_empty() {
+        return Err(format!(
+            "Refusing to write an invalid config: {}",
+            introduced.join("; ")
+        ));
+    }
+    write_with_backup(path, &output)?;
+
+    read_config_file(path)?.ok_or_else(|| "Config file not found after writing".to_string())
+}
+
+fn apply_edit(
+    doc: &mut Value,
+    config: &DitLoopConfigFile,
+    edit: ConfigEdit,
+) -> Result<Splice, String> {
+    match edit {
+        ConfigEdit::AddProfile { key, profile } => {
+            if config.profiles.contains_key(&key) {
+                return Err(format!("Profile '{}' already exists", key));
+            }
+            let entry = Value::Mapping(without_empty(entry_mapping(&profile)?));
+            let rendered = render_profile(&key, &entry)?;
+            section_mapping(doc, "profiles")?.insert(Value::String(key), entry);
+            Ok(Splice::Insert {
+                section: "profiles",
+                entry: rendered,
+            })
+        }
+        ConfigEdit::UpdateProfile { key, profile } => {
+            let profiles = section_mapping(doc, "profiles")?;
+            let index = profiles
+                .keys()
+                .position(|k| k.as_str() == Some(key.as_str()))
+                .ok_or_else(|| format!("Profile '{}' not found", key))?;
+            let entry = profiles
+                .get_mut(key.as_str())
+                .ok_or_else(|| format!("Profile '{}' not found", key))?;
+            merge_entry(entry, entry_mapping(&profile)?);
+            Ok(Splice::Replace {
+                section: "profiles",
+                index,
+                entry: render_profile(&key, entry)?,
+            })
+        }
+        ConfigEdit::RemoveProfile { key } => {
+            let profiles = section_mapping(doc, "profiles")?;
+            let index = profiles
+                .keys()
+                .position(|k| k.as_str() == Some(key.as_str()))
+                .ok_or_else(|| format!("Profile '{}' not found", key))?;
+            profiles.shift_remove(key.as_str());
+            Ok(Splice::Remove {
+                section: "profiles",
+                index,
+            })
+        }
+        ConfigEdit::AddWorkspace { workspace } => {
+            if config.workspaces.iter().any(|ws| ws.name == workspace.name) {
+                return Err(format!("Workspace '{}' already exists", workspace.name));
+            }
+            let entry = Value::Mapping(without_empty(workspace_fields(&workspace, None)?));
+            let rendered = render_workspace(&entry)?;
+            section_sequence(doc, "workspaces")?.push(entry);
+            Ok(Splice::Insert {
+                section: "workspaces",
+                entry: rendered,
+            })
+        }
+        ConfigEdit::UpdateWorkspace { name, workspace } => {
+            let index = workspace_index(config, &name)?;
+            let entry = section_sequence(doc, "workspaces")?
+                .get_mut(index)
+                .ok_or_else(|| format!("Workspace '{}' not found", name))?;
+            let update = workspace_fields(&workspace, entry.as_mapping())?;
+            merge_entry(entry, update);
+            Ok(Splice::Replace {
+                section: "workspaces",
+                index,
+                entry: render_workspace(entry)?,
+            })
+        }
+        ConfigEdit::RemoveWorkspace { name } => {
+            let index = workspace_index(config, &name)?;
+            section_sequence(doc, "workspaces")?.remove(index);
+            Ok(Splice::Remove {
+                section: "workspaces",
+                index,
+            })
+        }
+    }
+}
+
+fn workspace_index(config: &DitLoopConfigFile, name: &str) -> Result<usize, String> {
+    config
+        .workspaces
+        .iter()
+        .position(|ws| ws.name == name)
+        .ok_or_else(|| format!("Workspace '{}' not found", name))
+}
+
+/// Messages of error diagnostics; warnings such as missing paths don't
+/// block a write.
+fn errors(diagnostics: &[ConfigDiagnostic]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == DiagnosticSeverity::Error)
+        .map(|d| d.message.clone())
+        .collect()
+}
+
+/// The top-level mapping `key`, created if absent.
+fn section_mapping<'a>(doc: &'a mut Value, key: &str) -> Result<&'a mut Mapping, String> {
+    let root = doc.as_mapping_mut().ok_or("Config is not a mapping")?;
+    let section = root
+        .entry(Value::String(key.to_string()))
+        .or_insert(Value::Null);
+    if section.is_null() {
+        *section = Value::Mapping(Mapping::new());
+    }
+    section
+        .as_mapping_mut()
+        .ok_or_else(|| format!("`{}` is not a mapping", key))
+}
+
+/// The top-level sequence `key`, created if absent.
+fn section_sequence<'a>(doc: &'a mut Value, key: &str) -> Result<&'a mut Vec<Value>, String> {
+    let root = doc.as_mapping_mut().ok_or("Config is not a mapping")?;
+    let section = root
+        .entry(Value::String(key.to_string()))
+        .or_insert(Value::Null);
+    if section.is_null() {
+        *section = Value::Sequence(Vec::new());
+    }
+    section
+        .as_sequence_mut()
+        .ok_or_else(|| format!("`{}` is not a list", key))
+}
+
+fn entry_mapping<T: Serialize>(entry: &T) -> Result<Mapping, String> {
+    match serde_yaml::to_value(entry) {
+        Ok(Value::Mapping(mapping)) => Ok(mapping),
+        Ok(_) => Err("Config entry is not a mapping".to_string()),
+        Err(e) => Err(format!("Failed to serialize config entry: {}", e)),
+    }
+}
+
+/// A workspace's fields, leaving out those at their serde default, such
+/// as `type: single` and `aidf: false`, unless `existing` spells them out.
+fn workspace_fields(
+    workspace: &WorkspaceConfig,
+    existing: Option<&Mapping>,
+) -> Result<Mapping, String> {
+    let defaults: WorkspaceConfig = serde_yaml::from_str("{name: '', path: ''}")
+        .map_err(|e| format!("Failed to build workspace defaults: {}", e))?;
+    let defaults = entry_mapping(&defaults)?;
+    let mut fields = entry_mapping(workspace)?;
+    fields.retain(|key, value| {
+        defaults.get(key) != Some(value) || existing.is_some_and(|e| e.contains_key(key))
+    });
+    Ok(fields)
+}
+
+/// Drop unset fields, empty strings and empty lists, which serde leaves as
+/// `null`, `""` and `[]`.
+fn without_empty(mut mapping: Mapping) -> Mapping {
+    mapping.retain(|_, value| !is_empty_value(value));
+    mapping
+}
+
+fn is_empty_value(value: &Value) -> bool {
+    value.is_null()
+        || value.as_str().is_some_and(str::is_empty)
+        || value.as_sequence().is_some_and(Vec::is_empty)
+}
+
+/// Update an entry in place: existing keys keep their position, new keys
+/// are appended, and fields that became empty are dropped. Keys this
+/// version does not know about are left alone.
+fn merge_entry(entry: &mut Value, update: Mapping) {
+    let Value::Mapping(existing) = entry else {
+        *entry = Value::Mapping(without_empty(update));
+        return;
+    };
+    for (key, value) in update {
+        if is_empty_value(&value) {
+            existing.shift_remove(&key);
+        } else if let Some(slot) = existing.get_mut(&key) {
+            *slot = value;
+        } else {
+            existing.insert(key, value);
+        }
+    }
+}
+
+pub(crate) fn render_profile(key: &str, entry: &Value) -> Result<String, String> {
+    let mut mapping = Mapping::new();
+    mapping.insert(Value::String(key.to_string()), entry.clone());
+    serde_yaml::to_string(&mapping).map_err(|e| format!("Failed to serialize profile: {}", e))
+}
+
+fn render_workspace(entry: &Value) -> Result<String, String> {
+    serde_yaml::to_string(&Value::Sequence(vec![entry.clone()]))
+        .map_err(|e| format!("Failed to serialize workspace: {}", e))
+}
+
+/// A top-level block section of the file, e.g. `profiles:` and the lines
+/// below it.
+pub(crate) struct Section {
+    pub header: usize,
+    /// Line ranges of each entry, without trailing blank or comment lines.
+    pub entries: Vec<(usize, usize)>,
+    pub indent: Option<usize>,
+}
+
+pub(crate) enum SectionLookup {
+    Missing,
+    Found(Section),
+    /// Flow style, an inline value or irregular indentation.
+    Unsupported,
+}
+
+pub(crate) fn find_section(lines: &[&str], key: &str, sequence: bool) -> SectionLookup {
+    let Some(header) = lines.iter().position(|line| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.starts_with(':'))
+    }) else {
+        return SectionLookup::Missing;
+    };
+    let value = lines[header][key.len() + 1..].trim();
+    if !value.is_empty() && !value.starts_with('#') {
+        return SectionLookup::Unsupported;
+    }
+
+    let mut end = header + 1;
+    while end < lines.len() {
+        let line = lines[end];
+        let top_level = !line.is_empty()
+            && !line.starts_with(char::is_whitespace)
+            && !line.starts_with('#')
+            && !line.starts_with('-');
+        if top_level {
+            break;
+        }
+        end += 1;
+    }
+
+    let mut indent = None;
+    let mut starts = Vec::new();
+    for (i, line) in lines.iter().enumerate().take(end).skip(header + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_indent = line.len() - trimmed.len();
+        let entry_indent = *indent.get_or_insert(line_indent);
+        if line_indent < entry_indent {
+            return SectionLookup::Unsupported;
+        }
+        if line_indent == entry_indent {
+            if trimmed.starts_with('-') != sequence {
+                return SectionLookup::Unsupported;
+            }
+            starts.push(i);
+        }
+    }
+
+    let entries = starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let mut stop = starts.get(n + 1).copied().unwrap_or(end);
+            while stop > start + 1 {
+                let trimmed = lines[stop - 1].trim_start();
+                if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                    break;
+                }
+                stop -= 1;
+            }
+            (start, stop)
+        })
+        .collect();
+    SectionLookup::Found(Section {
+        header,
+        entries,
+        indent,
+    })
+}
+
+/// Apply a splice to the original text, or `None` if the layout is not one
+/// that can be edited line by line.
+fn splice_text(text: &str, splice: &Splice) -> Option<String> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let (section, index) = match splice {
+        Splice::Insert { section, .. } => (*section, None),
+        Splice::Replace { section, index, .. } | Splice::Remove { section, index } => {
+            (*section, Some(*index))
+        }
+    };
+    let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let lookup = find_section(&borrowed, section, section == "workspaces");
+
+    let found = match (lookup, splice) {
+        (SectionLookup::Found(found), _) => found,
+        (SectionLookup::Missing, Splice::Insert { entry, .. }) => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("{}:", section));
+            lines.extend(indent_lines(entry, DEFAULT_INDENT));
+            return Some(join_lines(&lines));
+        }
+        _ => return None,
+    };
+    let indent = found.indent.unwrap_or(DEFAULT_INDENT);
+
+    match splice {
+        Splice::Insert { entry, .. } => {
+            let at = found
+                .entries
+                .last()
+                .map_or(found.header + 1, |&(_, end)| end);
+            lines.splice(at..at, indent_lines(entry, indent));
+        }
+        Splice::Replace { entry, .. } => {
+            let (start, end) = *found.entries.get(index?)?;
+            lines.splice(start..end, indent_lines(entry, indent));
+        }
+        Splice::Remove { .. } => {
+            let (start, end) = *found.entries.get(index?)?;
+            lines.drain(start..end);
+        }
+    }
+    Some(join_lines(&lines))
+}
+
+pub(crate) fn indent_lines(entry: &str, indent: usize) -> Vec<String> {
+    let pad = " ".repeat(indent);
+    entry
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", pad, line)
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn join_lines(lines: &[String]) -> String {
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Write through a temp file in the same directory and rename it into
+/// place, keeping the previous file as `<name>.bak`.
+///
+/// A symlinked config is written at its target so the link survives, and
+/// the new file keeps the old one's permissions.
+pub(crate) fn write_with_backup(path: &Path, content: &str) -> Result<(), String> {
+    let resolved = fs::canonicalize(path).ok();
+    let path = resolved.as_deref().unwrap_or(path);
+    let dir = path.parent().ok_or("Invalid config path")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let temp = sibling(path, "tmp");
+    let permissions = fs::metadata(path).ok().map(|meta| meta.permissions());
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(format!("Failed to write config: {}", e));
+    }
+
+    if path.exists() {
+        fs::copy(path, sibling(path, "bak"))
+            .map_err(|e| format!("Failed to back up config: {}", e))?;
+    }
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to replace config: {}", e)
+    })
+}
+
+/// `path` with `.suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+# DitLoop config
+profiles:
+  # Day job
+  work:
+    name: Work User
+    email: work@example.com
+    platform: github
+  personal:
+    name: Me
+    email: me@example.com
+
+# Repositories
+workspaces:
+  - name: api
+    path: ~/code/api
+    profile: work
+    autoDiscover: false # kept by the TypeScript side
+  - name: blog
+    path: ~/code/blog
+    profile: personal
+";
+
+    fn profile(name: &str, email: &str) -> ProfileConfig {
+        ProfileConfig {
+            name: name.into(),
+            email: email.into(),
+            ssh_host: None,
+            ssh_key: None,
+            platform: None,
+            signing: None,
+        }
+    }
+
+    fn workspace(name: &str, profile: &str) -> WorkspaceConfig {
+        WorkspaceConfig {
+            name: name.into(),
+            path: format!("~/code/{}", name),
+            r#type: "single".into(),
+            profile: profile.into(),
+            aidf: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            settings: Default::default(),
+        }
+    }
+
+    fn config_file(content: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        fs::write(&path, content).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_edits_preserve_comments_and_order() {
+        let (_dir, path) = config_file(CONFIG);
+
+        let edit = ConfigEdit::AddProfile {
+            key: "oss".into(),
+            profile: profile("Oss", "oss@example.com"),
+        };
+        edit_config_file(&path, edit).unwrap();
+        let edit = ConfigEdit::UpdateWorkspace {
+            name: "api".into(),
+            workspace: workspace("api", "oss"),
+        };
+        edit_config_file(&path, edit).unwrap();
+        let mut grouped = workspace("blog", "personal");
+        grouped.exclude = vec!["archive".into()];
+        edit_config_file(
+            &path,
+            ConfigEdit::UpdateWorkspace {
+                name: "blog".into(),
+                workspace: grouped,
+            },
+        )
+        .unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("- archive"));
+        edit_config_file(
+            &path,
+            ConfigEdit::UpdateWorkspace {
+                name: "blog".into(),
+                workspace: workspace("blog", "personal"),
+            },
+        )
+        .unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("exclude"));
+        let edit = ConfigEdit::RemoveWorkspace {
+            name: "blog".into(),
+        };
+        let config = edit_config_file(&path, edit).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("# DitLoop config\nprofiles:\n  # Day job\n  work:"));
+        assert!(text.contains("\n# Repositories\nworkspaces:\n"));
+        assert!(text.contains("    email: oss@example.com\n"));
+        assert!(text.contains("autoDiscover: false"));
+        assert!(!text.contains("type: single") && !text.contains("aidf: false"));
+        assert!(!text.contains("blog"));
+        let work = text.find("  work:").unwrap();
+        let oss = text.find("  oss:").unwrap();
+        assert!(work < oss);
+
+        assert_eq!(config.workspaces.len(), 1);
+        assert_eq!(config.workspaces[0].profile, "oss");
+        assert_eq!(config.profiles.len(), 3);
+        assert!(sibling(&path, "bak").exists());
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn test_refuses_invalid_config() {
+        let (_dir, path) = config_file(CONFIG);
+
+        let edit = ConfigEdit::RemoveProfile { key: "work".into() };
+        let err = edit_config_file(&path, edit).unwrap_err();
+        assert!(err.contains("unknown profile 'work'"), "{}", err);
+        let edit = ConfigEdit::AddWorkspace {
+            workspace: workspace("api", "work"),
+        };
+        assert!(edit_config_file(&path, edit).is_err());
+        let edit = ConfigEdit::AddProfile {
+            key: "bad".into(),
+            profile: profile("Bad", "not-an-email"),
+        };
+        assert!(edit_config_file(&path, edit).is_err());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+        assert!(!sibling(&path, "bak").exists());
+    }
+
+    #[test]
+    fn test_edit_creates_missing_sections_and_falls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        let edit = ConfigEdit::AddProfile {
+            key: "work".into(),
+            profile: profile("Work User", "work@example.com"),
+        };
+        edit_config_file(&path, edit).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "profiles:\n  work:\n    name: Work User\n    email: work@example.com\n"
+        );
+        let edit = ConfigEdit::AddWorkspace {
+            workspace: workspace("api", "work"),
+        };
+        edit_config_file(&path, edit).unwrap();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("\nworkspaces:\n  - name: api\n    path: ~/code/api\n    profile: work\n"));
+
+        // Flow style can't be edited line by line and is re-serialized.
+        let (_dir, path) = config_file("profiles: {work: {name: W, email: w@example.com}}\n");
+        let edit = ConfigEdit::AddProfile {
+            key: "oss".into(),
+            profile: profile("Oss", "oss@example.com"),
+        };
+        let config = edit_config_file(&path, edit).unwrap();
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles["work"].email, "w@example.com");
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.find("work:").unwrap() < text.find("oss:").unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_keeps_symlink_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, target) = config_file(CONFIG);
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.path().join("link.yml");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let edit = ConfigEdit::AddProfile {
+            key: "oss".into(),
+            profile: profile("Oss", "oss@example.com"),
+        };
+        edit_config_file(&link, edit).unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(fs::read_to_string(&target)
+            .unwrap()
+            .contains("oss@example.com"));
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(sibling(&target, "bak").exists());
+    }
+}
@@ -0,0 +1,260 @@
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+
+use super::config::{config_file_path, duplicate_profile_error};
+use super::config_edit::{
+    find_section, indent_lines, join_lines, render_profile, write_with_backup, SectionLookup,
+    DEFAULT_INDENT,
+};
+
+/// Layout version written by this app. Files without a `version` key are
+/// version 0.
+pub(crate) const CURRENT_VERSION: u64 = 1;
+
+/// Result of [`migrate_ditloop_config`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMigration {
+    pub from_version: u64,
+    pub to_version: u64,
+    /// False when the file was already in the current layout.
+    pub migrated: bool,
+}
+
+/// Rewrite the config file in the current layout, keeping the previous
+/// file as `<name>.bak`.
+#[tauri::command]
+pub fn migrate_ditloop_config() -> Result<ConfigMigration, String> {
+    let path = config_file_path()?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read config: {}", e)),
+    };
+    let from_version = config_version(&text)?;
+    let migrated = migrate_config_text(&text)?;
+    if let Some(output) = &migrated {
+        write_with_backup(&path, output)?;
+    }
+    Ok(ConfigMigration {
+        from_version,
+        to_version: if migrated.is_some() {
+            CURRENT_VERSION
+        } else {
+            from_version
+        },
+        migrated: migrated.is_some(),
+    })
+}
+
+/// One layout change, upgrading a document from the version at its index.
+/// Returns whether anything changed.
+type Migration = fn(&mut Value) -> Result<bool, String>;
+
+const MIGRATIONS: &[Migration] = &[profiles_list_to_map];
+
+/// Version 0 → 1: `profiles` as a list of `{name, gitUser, ...}` becomes a
+/// mapping keyed by `name`, with `gitUser` as the profile's `name`. A name
+/// used twice is refused rather than dropping one of the entries.
+fn profiles_list_to_map(doc: &mut Value) -> Result<bool, String> {
+    let Some(Value::Sequence(list)) = doc.get("profiles") else {
+        return Ok(false);
+    };
+    let mut profiles = Mapping::new();
+    for (index, entry) in list.iter().enumerate() {
+        let mut entry = entry
+            .as_mapping()
+            .cloned()
+            .ok_or_else(|| format!("profiles[{}] is not a mapping", index))?;
+        let key = entry
+            .shift_remove("name")
+            .ok_or_else(|| format!("profiles[{}] has no name", index))?;
+        if profiles.contains_key(&key) {
+            return Err(duplicate_profile_error(
+                index,
+                key.as_str().unwrap_or_default(),
+            ));
+        }
+        let git_user = entry.shift_remove("gitUser").unwrap_or_else(|| key.clone());
+        let mut profile = Mapping::new();
+        profile.insert(Value::from("name"), git_user);
+        profile.extend(entry);
+        profiles.insert(key, Value::Mapping(profile));
+    }
+    doc["profiles"] = Value::Mapping(profiles);
+    Ok(true)
+}
+
+/// The file's layout version.
+pub(crate) fn config_version(text: &str) -> Result<u64, String> {
+    let doc: Value =
+        serde_yaml::from_str(text).map_err(|e| format!("Failed to parse config: {}", e))?;
+    match doc.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| "Config version is not a number".to_string()),
+    }
+}
+
+/// Upgrade config text to the current layout, or `None` if no migration
+/// changes it.
+///
+/// Migrated sections are rewritten entry by entry so the rest of the file,
+/// comments included, is kept; a layout that can't be edited that way is
+/// re-serialized whole. A file from a newer version is refused rather than
+/// guessed at.
+pub(crate) fn migrate_config_text(text: &str) -> Result<Option<String>, String> {
+    let version = config_version(text)?;
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "Config version {} is newer than this app supports ({})",
+            version, CURRENT_VERSION
+        ));
+    }
+    let mut doc: Value =
+        serde_yaml::from_str(text).map_err(|e| format!("Failed to parse config: {}", e))?;
+    if doc.is_null() {
+        return Ok(None);
+    }
+
+    let mut changed = false;
+    for migration in &MIGRATIONS[version as usize..] {
+        changed |= migration(&mut doc)?;
+    }
+    if !changed {
+        return Ok(None);
+    }
+    doc["version"] = Value::from(CURRENT_VERSION);
+
+    let output = match rewrite_text(text, &doc)
+        .filter(|out| serde_yaml::from_str::<Value>(out).ok().as_ref() == Some(&doc))
+    {
+        Some(output) => output,
+        None => {
+            let mut ordered = Mapping::new();
+            ordered.insert(Value::from("version"), Value::from(CURRENT_VERSION));
+            if let Value::Mapping(mapping) = doc {
+                ordered.extend(mapping);
+            }
+            serde_yaml::to_string(&ordered)
+                .map_err(|e| format!("Failed to serialize config: {}", e))?
+        }
+    };
+    Ok(Some(output))
+}
+
+/// Rewrite the profile entries and set the version line in the original
+/// text, or `None` if the layout doesn't allow it.
+fn rewrite_text(text: &str, doc: &Value) -> Option<String> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+
+    let borrowed: Vec<&str> = lines.iter().map(String::as_str).collect();
+    if let SectionLookup::Found(section) = find_section(&borrowed, "profiles", true) {
+        let profiles = doc.get("profiles")?.as_mapping()?;
+        if profiles.len() != section.entries.len() {
+            return None;
+        }
+        let indent = section.indent.filter(|&i| i > 0).unwrap_or(DEFAULT_INDENT);
+        let replacements: Vec<_> = section.entries.iter().zip(profiles).collect();
+        // Back to front, so earlier ranges stay valid.
+        for (&(start, end), (key, entry)) in replacements.into_iter().rev() {
+            let rendered = render_profile(key.as_str()?, entry).ok()?;
+            lines.splice(start..end, indent_lines(&rendered, indent));
+        }
+    }
+
+    let version_line = format!("version: {}", CURRENT_VERSION);
+    match lines.iter().position(|line| line.starts_with("version:")) {
+        Some(at) => lines[at] = version_line,
+        None => {
+            let at = lines
+                .iter()
+                .position(|line| {
+                    !line.is_empty()
+                        && !line.starts_with(char::is_whitespace)
+                        && !line.starts_with('#')
+                        && !line.starts_with("---")
+                })
+                .unwrap_or(lines.len());
+            lines.insert(at, version_line);
+        }
+    }
+    Some(join_lines(&lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::DitLoopConfigFile;
+
+    const README_CONFIG: &str = "\
+# DitLoop config
+workspaces:
+  - name: my-project
+    path: ~/projects/my-project
+
+profiles:
+  # Everyday identity
+  - name: personal
+    email: you@example.com
+    gitUser: Your Name
+    sshKey: ~/.ssh/id_ed25519
+  - name: work
+    email: you@company.com
+";
+
+    #[test]
+    fn test_migrates_list_profiles_in_place() {
+        let output = migrate_config_text(README_CONFIG).unwrap().unwrap();
+        assert_eq!(
+            output,
+            "\
+# DitLoop config
+version: 1
+workspaces:
+  - name: my-project
+    path: ~/projects/my-project
+
+profiles:
+  # Everyday identity
+  personal:
+    name: Your Name
+    email: you@example.com
+    sshKey: ~/.ssh/id_ed25519
+  work:
+    name: work
+    email: you@company.com
+"
+        );
+
+        let before: DitLoopConfigFile = serde_yaml::from_str(README_CONFIG).unwrap();
+        let after: DitLoopConfigFile = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(after.version, Some(CURRENT_VERSION));
+        assert_eq!(
+            serde_yaml::to_value(&before.profiles).unwrap(),
+            serde_yaml::to_value(&after.profiles).unwrap()
+        );
+        assert_eq!(migrate_config_text(&output).unwrap(), None);
+    }
+
+    #[test]
+    fn test_migration_skips_current_and_refuses_newer() {
+        let canonical = "profiles:\n  work:\n    name: Work\n    email: w@example.com\n";
+        assert_eq!(migrate_config_text(canonical).unwrap(), None);
+        assert_eq!(migrate_config_text("").unwrap(), None);
+        assert!(migrate_config_text("version: 99\n").is_err());
+        let duplicated = "profiles:\n  - name: work\n    email: a@example.com\n  - name: work\n    email: b@example.com\n";
+        assert_eq!(
+            migrate_config_text(duplicated).unwrap_err(),
+            "profiles[1]: duplicate profile name 'work'"
+        );
+
+        let flow = "profiles: [{name: work, email: w@example.com, gitUser: Work}]\n";
+        let output = migrate_config_text(flow).unwrap().unwrap();
+        assert!(output.starts_with("version: 1\n"));
+        let config: DitLoopConfigFile = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(config.profiles["work"].name, "Work");
+    }
+}
@@ -0,0 +1,412 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+
+use super::config::{
+    config_file_candidates, config_file_path, read_config_file, ConfigLoadResult,
+    DitLoopConfigFile, WorkspaceConfig,
+};
+use super::config_migrate::migrate_config_text;
+use super::config_validate::{validate_config_text, ConfigDiagnostic};
+
+/// Event emitted when the config file changes on disk.
+pub const CONFIG_CHANGED_EVENT: &str = "config:changed";
+
+/// Quiet period after the last filesystem event before the file is re-read.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Payload for [`CONFIG_CHANGED_EVENT`]: the reloaded config and what
+/// changed since the previous event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChanged {
+    pub config_path: String,
+    pub exists: bool,
+    pub config: DitLoopConfigFile,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    pub needs_migration: bool,
+    /// Profiles by key.
+    pub profiles: EntryChanges,
+    /// Workspaces by name.
+    pub workspaces: EntryChanges,
+}
+
+/// Keys of added, removed and modified entries, sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl EntryChanges {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// The parsed config file, shared by every command that reads it.
+///
+/// Reads are checked against the file's modification time and size, so a
+/// write that the watcher has not reported yet is still picked up.
+#[derive(Default)]
+pub struct ConfigState {
+    cache: Mutex<Option<CachedConfig>>,
+    watch: Mutex<Option<ConfigWatch>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct CachedConfig {
+    path: PathBuf,
+    /// Modification time and size when read; `None` if the file is missing.
+    stamp: Option<(SystemTime, u64)>,
+    result: ConfigLoadResult,
+    /// Why the file could not be read as a config, if it could not.
+    error: Option<String>,
+}
+
+/// A running config watch; dropping it stops the task, which owns the
+/// watcher.
+struct ConfigWatch {
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl Drop for ConfigWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ConfigState {
+    /// The config with its diagnostics, as `load_ditloop_config` returns it.
+    pub(crate) fn load(&self) -> Result<ConfigLoadResult, String> {
+        Ok(self.current(&config_file_path()?)?.result)
+    }
+
+    /// The parsed config, `None` if the file does not exist.
+    ///
+    /// Like [`read_config_file`], a file that does not parse is an error.
+    pub(crate) fn config(&self) -> Result<Option<DitLoopConfigFile>, String> {
+        let cached = self.current(&config_file_path()?)?;
+        match cached.error {
+            Some(error) => Err(error),
+            None if cached.result.exists => Ok(Some(cached.result.config)),
+            None => Ok(None),
+        }
+    }
+
+    /// The cached entry for `path`, re-read if the file changed since.
+    pub(crate) fn current(&self, path: &Path) -> Result<CachedConfig, String> {
+        let stamp = file_stamp(path);
+        if let Ok(cache) = self.cache.lock() {
+            if let Some(cached) = cache.as_ref() {
+                if cached.path == path && cached.stamp == stamp {
+                    return Ok(cached.clone());
+                }
+            }
+        }
+
+        let cached = read_cached(path, stamp)?;
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = Some(cached.clone());
+        }
+        Ok(cached)
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn read_cached(path: &Path, stamp: Option<(SystemTime, u64)>) -> Result<CachedConfig, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read config: {}", e)),
+    };
+    let diagnostics = text
+        .as_deref()
+        .map(validate_config_text)
+        .unwrap_or_default();
+    let needs_migration = text
+        .as_deref()
+        .is_some_and(|text| matches!(migrate_config_text(text), Ok(Some(_))));
+
+    let (config, error) = match read_config_file(path) {
+        Ok(config) => (config.unwrap_or_default(), None),
+        // The parse error is in the diagnostics as well.
+        Err(e) if !diagnostics.is_empty() => (DitLoopConfigFile::default(), Some(e)),
+        Err(e) => return Err(e),
+    };
+    Ok(CachedConfig {
+        path: path.to_path_buf(),
+        stamp,
+        result: ConfigLoadResult {
+            config,
+            config_path: path.to_string_lossy().to_string(),
+            exists: text.is_some(),
+            diagnostics,
+            needs_migration,
+        },
+        error,
+    })
+}
+
+/// Start watching every location the config file can be read from,
+/// emitting [`CONFIG_CHANGED_EVENT`] when the config in use changes. The
+/// path is resolved again on each change, so a file created at a location
+/// that takes precedence is picked up. Replaces a previous watch.
+pub(crate) fn start_config_watch(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<ConfigState>();
+    let mut last = state.load()?;
+
+    let handle = app.clone();
+    let watch = spawn_config_watch(config_file_candidates()?, move || {
+        let state = handle.state::<ConfigState>();
+        let Ok(current) = state.load() else {
+            return;
+        };
+        if let Some(changed) = diff_configs(&last, &current) {
+            let _ = handle.emit(CONFIG_CHANGED_EVENT, changed);
+        }
+        last = current;
+    })?;
+
+    if let Ok(mut slot) = state.watch.lock() {
+        *slot = Some(watch);
+    }
+    Ok(())
+}
+
+/// Watch the directories holding `candidates`, since editors often replace
+/// the file rather than write to it, and call `on_change` once per
+/// debounced batch of events touching one of them.
+///
+/// A directory that doesn't exist yet is watched through its nearest
+/// existing ancestor, and the watches move once it is created or removed.
+fn spawn_config_watch<F>(candidates: Vec<PathBuf>, mut on_change: F) -> Result<ConfigWatch, String>
+where
+    F: FnMut() + Send + 'static,
+{
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let relevant = candidates.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        // The file itself, or a directory on the way to it.
+        if event
+            .paths
+            .iter()
+            .any(|p| relevant.iter().any(|candidate| candidate.starts_with(p)))
+        {
+            let _ = sender.send(());
+        }
+    })
+    .map_err(|e| format!("Failed to start watcher: {}", e))?;
+
+    let mut watched = watch_dirs(&candidates);
+    for dir in &watched {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    }
+
+    let task = tauri::async_runtime::spawn(async move {
+        while receiver.recv().await.is_some() {
+            while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {}
+
+            let dirs = watch_dirs(&candidates);
+            for dir in watched.difference(&dirs) {
+                let _ = watcher.unwatch(dir);
+            }
+            for dir in dirs.difference(&watched) {
+                let _ = watcher.watch(dir, RecursiveMode::NonRecursive);
+            }
+            watched = dirs;
+            on_change();
+        }
+    });
+    Ok(ConfigWatch { task })
+}
+
+/// Each candidate's directory, or its nearest existing ancestor.
+fn watch_dirs(candidates: &[PathBuf]) -> BTreeSet<PathBuf> {
+    candidates
+        .iter()
+        .filter_map(|path| path.parent()?.ancestors().find(|dir| dir.is_dir()))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// What changed between two loads, or `None` if nothing did.
+pub(crate) fn diff_configs(
+    before: &ConfigLoadResult,
+    after: &ConfigLoadResult,
+) -> Option<ConfigChanged> {
+    let profiles = diff_entries(&before.config.profiles, &after.config.profiles);
+    let workspaces = diff_entries(
+        &workspaces_by_name(&before.config.workspaces),
+        &workspaces_by_name(&after.config.workspaces),
+    );
+    let unchanged = profiles.is_empty()
+        && workspaces.is_empty()
+        && before.config_path == after.config_path
+        && before.exists == after.exists
+        && before.diagnostics == after.diagnostics
+        && before.needs_migration == after.needs_migration;
+    if unchanged {
+        return None;
+    }
+    Some(ConfigChanged {
+        config_path: after.config_path.clone(),
+        exists: after.exists,
+        config: after.config.clone(),
+        diagnostics: after.diagnostics.clone(),
+        needs_migration: after.needs_migration,
+        profiles,
+        workspaces,
+    })
+}
+
+fn workspaces_by_name(workspaces: &[WorkspaceConfig]) -> HashMap<String, &WorkspaceConfig> {
+    workspaces.iter().map(|ws| (ws.name.clone(), ws)).collect()
+}
+
+fn diff_entries<V: PartialEq>(
+    before: &HashMap<String, V>,
+    after: &HashMap<String, V>,
+) -> EntryChanges {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut changes = EntryChanges::default();
+    for key in keys {
+        match (before.get(key), after.get(key)) {
+            (None, Some(_)) => changes.added.push(key.clone()),
+            (Some(_), None) => changes.removed.push(key.clone()),
+            (Some(old), Some(new)) if old != new => changes.modified.push(key.clone()),
+            _ => {}
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+profiles:
+  work:
+    name: Work User
+    email: work@example.com
+  personal:
+    name: Me
+    email: me@example.com
+workspaces:
+  - name: api
+    path: /tmp
+    profile: work
+";
+
+    #[test]
+    fn test_cache_rereads_changed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        let state = ConfigState::default();
+        assert!(!state.current(&path).unwrap().result.exists);
+
+        fs::write(&path, CONFIG).unwrap();
+        let first = state.current(&path).unwrap().result;
+        assert_eq!(first.config.profiles.len(), 2);
+
+        fs::write(&path, "profiles: [unclosed\n").unwrap();
+        let cached = state.current(&path).unwrap();
+        assert!(cached.error.is_some());
+        assert!(cached.result.exists);
+        assert_eq!(cached.result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        fs::write(&path, CONFIG).unwrap();
+        let before = read_cached(&path, None).unwrap().result;
+        assert!(diff_configs(&before, &before).is_none());
+
+        let edited = CONFIG
+            .replace("name: Me", "name: Someone Else")
+            .replace(
+                "  work:\n    name: Work User\n    email: work@example.com\n",
+                "",
+            )
+            .replace("profile: work", "profile: personal")
+            + "  - name: web\n    path: /tmp\n    profile: personal\n";
+        fs::write(&path, edited).unwrap();
+        let after = read_cached(&path, None).unwrap().result;
+
+        let changed = diff_configs(&before, &after).unwrap();
+        assert_eq!(
+            changed.profiles,
+            EntryChanges {
+                added: vec![],
+                removed: vec!["work".to_string()],
+                modified: vec!["personal".to_string()],
+            }
+        );
+        assert_eq!(
+            changed.workspaces,
+            EntryChanges {
+                added: vec!["web".to_string()],
+                removed: vec![],
+                modified: vec!["api".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yml");
+        fs::write(&path, CONFIG).unwrap();
+        let (sender, mut changes) = mpsc::unbounded_channel();
+        let preferred = dir.path().join("ditloop").join("config.yml");
+        let candidates = vec![preferred.clone(), path.clone()];
+        let _watch = spawn_config_watch(candidates, move || {
+            let _ = sender.send(());
+        })
+        .unwrap();
+
+        fs::write(dir.path().join("other.yml"), "ignored").unwrap();
+        let temp = dir.path().join("config.yml.tmp");
+        fs::write(&temp, CONFIG.replace("Me", "You")).unwrap();
+        fs::rename(&temp, &path).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // A location that didn't exist when the watch started.
+        fs::create_dir(preferred.parent().unwrap()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        fs::write(&preferred, CONFIG).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), changes.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
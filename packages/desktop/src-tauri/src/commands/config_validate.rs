@@ -0,0 +1,749 @@
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config::{
+    config_file_path, duplicate_profile_error, expand_home, DitLoopConfigFile, ProfileConfig,
+    WorkspaceConfig,
+};
+use super::config_edit::{find_section, Section, SectionLookup};
+use super::config_migrate::CURRENT_VERSION;
+
+/// Top-level keys, including those only the TypeScript packages read.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "version",
+    "profiles",
+    "workspaces",
+    "trustedRepos",
+    "defaults",
+    "server",
+    "providers",
+    "notifications",
+];
+
+const PROFILE_KEYS: &[&str] = &["name", "email", "sshHost", "sshKey", "platform", "signing"];
+
+/// Profile keys in the older list layout.
+const LISTED_PROFILE_KEYS: &[&str] = &[
+    "name", "gitUser", "email", "sshHost", "sshKey", "platform", "signing",
+];
+
+const WORKSPACE_KEYS: &[&str] = &[
+    "name",
+    "path",
+    "type",
+    "profile",
+    "aidf",
+    "autoDiscover",
+    "include",
+    "exclude",
+    "emailDomain",
+    "aiTool",
+    "aiArgs",
+    "protectedBranches",
+    "editor",
+];
+
+const WORKSPACE_TYPES: &[&str] = &["single", "group"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverity {
+    /// The config can't be used as written.
+    Error,
+    /// The config works but probably not as intended.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCode {
+    /// The file is not valid YAML.
+    Syntax,
+    /// A value has the wrong shape, or a required field is missing or empty.
+    InvalidValue,
+    UnknownKey,
+    UnknownProfile,
+    /// Two list-layout profiles share a name.
+    DuplicateProfileName,
+    DuplicateWorkspaceName,
+    DuplicateWorkspacePath,
+    InvalidEmail,
+    MissingPath,
+    InvalidType,
+    /// The file uses an older layout that a migration would rewrite.
+    OutdatedLayout,
+    /// The file was written by a newer version of the app.
+    UnsupportedVersion,
+}
+
+/// A problem found in the config file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    /// Location in the config, e.g. `workspaces[1].profile`.
+    pub path: String,
+    /// 1-based line of the offending key or value, when it could be found.
+    pub line: Option<usize>,
+    /// 1-based column on `line`.
+    pub column: Option<usize>,
+}
+
+/// Validate the config file, reporting every problem found.
+#[tauri::command]
+pub fn validate_ditloop_config() -> Result<Vec<ConfigDiagnostic>, String> {
+    match fs::read_to_string(config_file_path()?) {
+        Ok(text) => Ok(validate_config_text(&text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read config: {}", e)),
+    }
+}
+
+/// Validate config file contents.
+///
+/// A syntax error is reported alone, since nothing past it can be checked
+/// reliably. Profiles and workspaces are parsed one entry at a time, so an
+/// entry of the wrong shape is reported along with the other problems.
+pub(crate) fn validate_config_text(text: &str) -> Vec<ConfigDiagnostic> {
+    let doc: Value = match serde_yaml::from_str(text) {
+        Ok(doc) => doc,
+        Err(e) => return vec![yaml_error(DiagnosticCode::Syntax, &e)],
+    };
+    if doc.is_null() {
+        return Vec::new();
+    }
+
+    let mut validator = Validator {
+        locator: Locator::new(text, doc.get("profiles").is_some_and(Value::is_sequence)),
+        diagnostics: Vec::new(),
+        entry_errors: false,
+    };
+    validator.check_top_level(&doc);
+    validator.check_layout(&doc);
+    let profiles = validator.check_profiles(&doc);
+    validator.check_workspaces(&doc, &profiles);
+    // Whatever the entries don't explain, such as a section of the wrong
+    // type.
+    if !validator.entry_errors {
+        if let Err(e) = serde_yaml::from_str::<DitLoopConfigFile>(text) {
+            validator
+                .diagnostics
+                .push(yaml_error(DiagnosticCode::InvalidValue, &e));
+        }
+    }
+
+    let mut diagnostics = validator.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line.unwrap_or(usize::MAX), d.column));
+    diagnostics
+}
+
+/// Parse a `profiles` section holding a single entry, in either layout.
+fn parse_profile(section: Value) -> Result<Option<ProfileConfig>, serde_yaml::Error> {
+    let mut root = serde_yaml::Mapping::new();
+    root.insert(Value::from("profiles"), section);
+    serde_yaml::from_value::<DitLoopConfigFile>(Value::Mapping(root))
+        .map(|config| config.profiles.into_values().next())
+}
+
+fn yaml_error(code: DiagnosticCode, error: &serde_yaml::Error) -> ConfigDiagnostic {
+    let location = error.location();
+    ConfigDiagnostic {
+        severity: DiagnosticSeverity::Error,
+        code,
+        message: error.to_string(),
+        path: String::new(),
+        line: location.as_ref().map(|l| l.line()),
+        column: location.as_ref().map(|l| l.column()),
+    }
+}
+
+struct Validator<'a> {
+    locator: Locator<'a>,
+    diagnostics: Vec<ConfigDiagnostic>,
+    /// An entry failed to parse or repeated a profile name, which is also
+    /// why the file as a whole fails to.
+    entry_errors: bool,
+}
+
+impl Validator<'_> {
+    fn push(
+        &mut self,
+        severity: DiagnosticSeverity,
+        code: DiagnosticCode,
+        message: String,
+        path: String,
+        position: Option<(usize, usize)>,
+    ) {
+        self.diagnostics.push(ConfigDiagnostic {
+            severity,
+            code,
+            message,
+            path,
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        });
+    }
+
+    fn error(
+        &mut self,
+        code: DiagnosticCode,
+        message: String,
+        path: String,
+        position: Option<(usize, usize)>,
+    ) {
+        self.push(DiagnosticSeverity::Error, code, message, path, position);
+    }
+
+    fn check_top_level(&mut self, doc: &Value) {
+        let Some(root) = doc.as_mapping() else {
+            return;
+        };
+        for key in root.keys().filter_map(Value::as_str) {
+            if !TOP_LEVEL_KEYS.contains(&key) {
+                let position = self.locator.top_level(key);
+                self.push(
+                    DiagnosticSeverity::Warning,
+                    DiagnosticCode::UnknownKey,
+                    format!("Unknown key '{}'", key),
+                    key.to_string(),
+                    position,
+                );
+            }
+        }
+    }
+
+    fn check_layout(&mut self, doc: &Value) {
+        if let Some(version) = doc.get("version") {
+            if version.as_u64().is_none_or(|v| v > CURRENT_VERSION) {
+                let position = self.locator.top_level("version");
+                self.error(
+                    DiagnosticCode::UnsupportedVersion,
+                    format!(
+                        "Config version {} is not supported; expected {} or lower",
+                        serde_yaml::to_string(version).unwrap_or_default().trim(),
+                        CURRENT_VERSION
+                    ),
+                    "version".to_string(),
+                    position,
+                );
+            }
+        }
+        if doc.get("profiles").is_some_and(Value::is_sequence) {
+            let position = self.locator.top_level("profiles");
+            self.push(
+                DiagnosticSeverity::Warning,
+                DiagnosticCode::OutdatedLayout,
+                "Profiles use the older list layout; migrate the config to key them by name"
+                    .to_string(),
+                "profiles".to_string(),
+                position,
+            );
+        }
+    }
+
+    /// Check each profile, in either layout, returning those that parse.
+    fn check_profiles(&mut self, doc: &Value) -> HashMap<String, ProfileConfig> {
+        let mut profiles = HashMap::new();
+        // Key, entry, path prefix and the field holding the git user name.
+        let entries: Vec<(Option<&str>, &Value, String, &str)> = match doc.get("profiles") {
+            Some(Value::Mapping(entries)) => entries
+                .iter()
+                .map(|(key, entry)| {
+                    let key = key.as_str();
+                    (
+                        key,
+                        entry,
+                        format!("profiles.{}", key.unwrap_or_default()),
+                        "name",
+                    )
+                })
+                .collect(),
+            Some(Value::Sequence(entries)) => entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| {
+                    let key = entry.get("name").and_then(Value::as_str);
+                    let user_field = if entry.get("gitUser").is_some() {
+                        "gitUser"
+                    } else {
+                        "name"
+                    };
+                    (key, entry, format!("profiles[{}]", index), user_field)
+                })
+                .collect(),
+            _ => return profiles,
+        };
+        let listed = doc.get("profiles").is_some_and(Value::is_sequence);
+        let known = if listed {
+            LISTED_PROFILE_KEYS
+        } else {
+            PROFILE_KEYS
+        };
+
+        for (index, (key, entry, base, user_field)) in entries.into_iter().enumerate() {
+            let entry_at = Entry::Profile(index);
+            let path = |field: &str| format!("{}.{}", base, field);
+            let section = match (listed, key) {
+                (true, _) => Value::Sequence(vec![entry.clone()]),
+                (false, Some(key)) => {
+                    let mut section = serde_yaml::Mapping::new();
+                    section.insert(Value::from(key), entry.clone());
+                    Value::Mapping(section)
+                }
+                (false, None) => continue,
+            };
+            let profile = match parse_profile(section) {
+                Ok(Some(profile)) => profile,
+                Ok(None) => continue,
+                Err(e) => {
+                    let position = self.locator.start(entry_at);
+                    self.entry_errors = true;
+                    self.error(DiagnosticCode::InvalidValue, e.to_string(), base, position);
+                    continue;
+                }
+            };
+            let Some(key) = key else {
+                continue;
+            };
+            if profiles.contains_key(key) {
+                let position = self.locator.field(entry_at, "name");
+                self.entry_errors = true;
+                self.error(
+                    DiagnosticCode::DuplicateProfileName,
+                    duplicate_profile_error(index, key),
+                    path("name"),
+                    position,
+                );
+                continue;
+            }
+
+            self.check_unknown_keys(entry, known, entry_at, &path(""));
+
+            if key.trim().is_empty() {
+                let position = self.locator.field(entry_at, "name");
+                self.error(
+                    DiagnosticCode::InvalidValue,
+                    "A profile has no name".to_string(),
+                    path("name"),
+                    position,
+                );
+            } else if profile.name.trim().is_empty() {
+                let position = self.locator.field(entry_at, user_field);
+                self.error(
+                    DiagnosticCode::InvalidValue,
+                    format!("Profile '{}' has no name", key),
+                    path(user_field),
+                    position,
+                );
+            }
+            if !is_valid_email(&profile.email) {
+                let position = self.locator.field(entry_at, "email");
+                self.error(
+                    DiagnosticCode::InvalidEmail,
+                    format!("Profile '{}' has an invalid email", key),
+                    path("email"),
+                    position,
+                );
+            }
+            profiles.insert(key.to_string(), profile);
+        }
+        profiles
+    }
+
+    fn check_workspaces(&mut self, doc: &Value, profiles: &HashMap<String, ProfileConfig>) {
+        let Some(entries) = doc.get("workspaces").and_then(Value::as_sequence) else {
+            return;
+        };
+        let mut names = HashSet::new();
+        let mut paths = HashSet::new();
+
+        for (index, value) in entries.iter().enumerate() {
+            let entry = Entry::Workspace(index);
+            let base = format!("workspaces[{}]", index);
+            let ws = match serde_yaml::from_value::<WorkspaceConfig>(value.clone()) {
+                Ok(ws) => ws,
+                Err(e) => {
+                    let position = self.locator.start(entry);
+                    self.entry_errors = true;
+                    self.error(DiagnosticCode::InvalidValue, e.to_string(), base, position);
+                    continue;
+                }
+            };
+            self.check_unknown_keys(value, WORKSPACE_KEYS, entry, &format!("{}.", base));
+            let field = |name: &str| format!("{}.{}", base, name);
+
+            if ws.name.trim().is_empty() {
+                let position = self.locator.field(entry, "name");
+                self.error(
+                    DiagnosticCode::InvalidValue,
+                    "A workspace has no name".to_string(),
+                    field("name"),
+                    position,
+                );
+            } else if !names.insert(ws.name.clone()) {
+                let position = self.locator.field(entry, "name");
+                self.error(
+                    DiagnosticCode::DuplicateWorkspaceName,
+                    format!("Duplicate workspace name '{}'", ws.name),
+                    field("name"),
+                    position,
+                );
+            }
+
+            if ws.path.trim().is_empty() {
+                let position = self.locator.field(entry, "path");
+                self.error(
+                    DiagnosticCode::InvalidValue,
+                    format!("Workspace '{}' has no path", ws.name),
+                    field("path"),
+                    position,
+                );
+            } else {
+                let expanded = PathBuf::from(expand_home(&ws.path));
+                if !paths.insert(normalize(&expanded)) {
+                    let position = self.locator.field(entry, "path");
+                    self.error(
+                        DiagnosticCode::DuplicateWorkspacePath,
+                        format!("Workspace '{}' repeats the path '{}'", ws.name, ws.path),
+                        field("path"),
+                        position,
+                    );
+                } else if !expanded.exists() {
+                    let position = self.locator.field(entry, "path");
+                    self.push(
+                        DiagnosticSeverity::Warning,
+                        DiagnosticCode::MissingPath,
+                        format!("Workspace '{}' path does not exist", ws.name),
+                        field("path"),
+                        position,
+                    );
+                }
+            }
+
+            if !ws.profile.is_empty() && !profiles.contains_key(&ws.profile) {
+                let position = self.locator.field(entry, "profile");
+                self.error(
+                    DiagnosticCode::UnknownProfile,
+                    format!(
+                        "Workspace '{}' uses unknown profile '{}'",
+                        ws.name, ws.profile
+                    ),
+                    field("profile"),
+                    position,
+                );
+            }
+            if !WORKSPACE_TYPES.contains(&ws.r#type.as_str()) {
+                let position = self.locator.field(entry, "type");
+                self.error(
+                    DiagnosticCode::InvalidType,
+                    format!(
+                        "Workspace '{}' has unknown type '{}'; expected single or group",
+                        ws.name, ws.r#type
+                    ),
+                    field("type"),
+                    position,
+                );
+            }
+        }
+    }
+
+    fn check_unknown_keys(&mut self, entry: &Value, known: &[&str], at: Entry, prefix: &str) {
+        let Some(entry) = entry.as_mapping() else {
+            return;
+        };
+        for key in entry.keys().filter_map(Value::as_str) {
+            if !known.contains(&key) {
+                let position = self.locator.field(at, key);
+                self.push(
+                    DiagnosticSeverity::Warning,
+                    DiagnosticCode::UnknownKey,
+                    format!("Unknown key '{}'", key),
+                    format!("{}{}", prefix, key),
+                    position,
+                );
+            }
+        }
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| PathBuf::from(path.to_string_lossy().trim_end_matches('/')))
+}
+
+/// A plausible address: one `@`, a local part and a dotted domain.
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
+}
+
+/// An entry of the `profiles` section or `workspaces` list, by position.
+#[derive(Clone, Copy)]
+enum Entry {
+    Profile(usize),
+    Workspace(usize),
+}
+
+/// Finds the text position of keys in a block-style config file.
+struct Locator<'a> {
+    lines: Vec<&'a str>,
+    profiles: Option<Section>,
+    /// Profiles use the older list layout.
+    listed_profiles: bool,
+    workspaces: Option<Section>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(text: &'a str, listed_profiles: bool) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let found = |lookup| match lookup {
+            SectionLookup::Found(section) => Some(section),
+            _ => None,
+        };
+        Locator {
+            profiles: found(find_section(&lines, "profiles", listed_profiles)),
+            listed_profiles,
+            workspaces: found(find_section(&lines, "workspaces", true)),
+            lines,
+        }
+    }
+
+    fn top_level(&self, key: &str) -> Option<(usize, usize)> {
+        self.lines
+            .iter()
+            .position(|line| split_key(line).is_some_and(|(k, _)| k == key))
+            .map(|index| (index + 1, 1))
+    }
+
+    /// Position of the start of an entry.
+    fn start(&self, entry: Entry) -> Option<(usize, usize)> {
+        let (section, index) = match entry {
+            Entry::Profile(index) => (self.profiles.as_ref()?, index),
+            Entry::Workspace(index) => (self.workspaces.as_ref()?, index),
+        };
+        let (start, _) = *section.entries.get(index)?;
+        Some((start + 1, section.indent? + 1))
+    }
+
+    /// Position of a field's value, or of its key when the value is on
+    /// later lines.
+    fn field(&self, entry: Entry, field: &str) -> Option<(usize, usize)> {
+        let (section, index, sequence) = match entry {
+            Entry::Profile(index) => (self.profiles.as_ref()?, index, self.listed_profiles),
+            Entry::Workspace(index) => (self.workspaces.as_ref()?, index, true),
+        };
+        let (start, end) = *section.entries.get(index)?;
+
+        let mut child_indent = None;
+        for (i, line) in self.lines.iter().enumerate().take(end).skip(start) {
+            let mut offset = line.len() - line.trim_start().len();
+            let mut content = line.trim_start();
+            if i == start {
+                if !sequence {
+                    continue;
+                }
+                let item = content.trim_start_matches('-').trim_start();
+                offset += content.len() - item.len();
+                content = item;
+            }
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            if *child_indent.get_or_insert(offset) != offset {
+                continue;
+            }
+            if let Some((key, value_offset)) = split_key(content) {
+                if key == field {
+                    let column = value_offset.map_or(offset, |v| offset + v);
+                    return Some((i + 1, column + 1));
+                }
+            }
+        }
+        self.start(entry)
+    }
+}
+
+/// Split `key: value` into the unquoted key and the value's offset, if
+/// there is a value on the line.
+fn split_key(content: &str) -> Option<(&str, Option<usize>)> {
+    let colon = content
+        .match_indices(':')
+        .map(|(i, _)| i)
+        .find(|&i| content[i + 1..].is_empty() || content[i + 1..].starts_with(' '))?;
+    let key = content[..colon]
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'');
+    let rest = &content[colon + 1..];
+    let value = rest.trim_start();
+    let value_offset =
+        (!value.is_empty() && !value.starts_with('#')).then(|| content.len() - value.len());
+    Some((key, value_offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(diagnostics: &[ConfigDiagnostic]) -> Vec<DiagnosticCode> {
+        diagnostics.iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_valid_config_has_no_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "profiles:\n  work:\n    name: Work User\n    email: work@example.com\nworkspaces:\n  - name: api\n    path: {}\n    profile: work\n    autoDiscover: true\n",
+            dir.path().display()
+        );
+        assert_eq!(validate_config_text(&yaml), []);
+    }
+
+    #[test]
+    fn test_reports_every_problem_with_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "\
+profiles:
+  work:
+    name: Work User
+    email: not-an-email
+    colour: blue
+workspaces:
+  - name: api
+    path: {root}
+    profile: work
+  - name: api
+    path: {root}
+    profile: personal
+    type: monorepo
+  - name: docs
+    path: /nonexistent/ditloop/docs
+    profile: work
+theme: dark
+",
+            root = dir.path().display()
+        );
+        let diagnostics = validate_config_text(&yaml);
+        assert_eq!(
+            codes(&diagnostics),
+            [
+                DiagnosticCode::InvalidEmail,
+                DiagnosticCode::UnknownKey,
+                DiagnosticCode::DuplicateWorkspaceName,
+                DiagnosticCode::DuplicateWorkspacePath,
+                DiagnosticCode::UnknownProfile,
+                DiagnosticCode::InvalidType,
+                DiagnosticCode::MissingPath,
+                DiagnosticCode::UnknownKey,
+            ]
+        );
+
+        let email = &diagnostics[0];
+        assert_eq!((email.line, email.column), (Some(4), Some(12)));
+        assert_eq!(email.path, "profiles.work.email");
+        assert_eq!(email.severity, DiagnosticSeverity::Error);
+        let duplicate = &diagnostics[2];
+        assert_eq!((duplicate.line, duplicate.column), (Some(10), Some(11)));
+        assert_eq!(duplicate.path, "workspaces[1].name");
+        let unknown_profile = &diagnostics[4];
+        assert_eq!(unknown_profile.line, Some(12));
+        assert_eq!(diagnostics[6].severity, DiagnosticSeverity::Warning);
+        assert_eq!(
+            (diagnostics[7].line, diagnostics[7].column),
+            (Some(17), Some(1))
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_has_location() {
+        let diagnostics = validate_config_text("profiles:\n  work: [unclosed\n");
+        assert_eq!(codes(&diagnostics), [DiagnosticCode::Syntax]);
+        assert!(diagnostics[0].line.is_some());
+
+        let diagnostics = validate_config_text("profiles:\n  work:\n    name: Work User\n");
+        assert_eq!(codes(&diagnostics), [DiagnosticCode::InvalidValue]);
+        assert!(diagnostics[0].message.contains("email"));
+
+        let diagnostics = validate_config_text(
+            "profiles:\n  work:\n    name: Work User\n    email: bad\nworkspaces:\n  - name: api\n    path: [nested]\n  - name: web\n    path: /nonexistent/ditloop/web\n    profile: work\n",
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            [
+                DiagnosticCode::InvalidEmail,
+                DiagnosticCode::InvalidValue,
+                DiagnosticCode::MissingPath,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_layout_version() {
+        let diagnostics = validate_config_text(
+            "profiles:\n  - name: work\n    email: work@example.com\n    gitUser: Work User\n",
+        );
+        assert_eq!(codes(&diagnostics), [DiagnosticCode::OutdatedLayout]);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[0].line, Some(1));
+
+        let diagnostics = validate_config_text(
+            "profiles:\n  - name: work\n    email: work@\n  - name: oss\n    gitUser: ''\n    email: oss@example.com\n",
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            [
+                DiagnosticCode::OutdatedLayout,
+                DiagnosticCode::InvalidEmail,
+                DiagnosticCode::InvalidValue,
+            ]
+        );
+        assert_eq!(diagnostics[1].path, "profiles[0].email");
+        assert_eq!(
+            (diagnostics[1].line, diagnostics[1].column),
+            (Some(3), Some(12))
+        );
+        assert_eq!(diagnostics[2].path, "profiles[1].gitUser");
+        assert_eq!(diagnostics[2].line, Some(5));
+
+        let diagnostics = validate_config_text(
+            "profiles:\n  - name: work\n    email: a@example.com\n  - name: work\n    email: b@example.com\n",
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            [
+                DiagnosticCode::OutdatedLayout,
+                DiagnosticCode::DuplicateProfileName,
+            ]
+        );
+        assert_eq!(diagnostics[1].path, "profiles[1].name");
+        assert_eq!(
+            (diagnostics[1].line, diagnostics[1].column),
+            (Some(4), Some(11))
+        );
+
+        let diagnostics = validate_config_text("version: 2\nprofiles: {}\n");
+        assert_eq!(codes(&diagnostics), [DiagnosticCode::UnsupportedVersion]);
+    }
+
+    #[test]
+    fn test_is_valid_email() {
+        assert!(is_valid_email("dev@example.com"));
+        assert!(is_valid_email("first.last+tag@sub.example.co"));
+        assert!(!is_valid_email("dev@localhost"));
+        assert!(!is_valid_email("dev@@example.com"));
+        assert!(!is_valid_email("@example.com"));
+        assert!(!is_valid_email("dev @example.com"));
+    }
+}
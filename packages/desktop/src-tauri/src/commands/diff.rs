@@ -0,0 +1,686 @@
+use serde::Serialize;
+
+use super::git::run_git;
+
+/// A file entry in a structured diff.
+#[derive(Debug, Serialize)]
+pub struct DiffFile {
+    /// Path before the change, `None` for added files.
+    pub old_path: Option<String>,
+    /// Path after the change, `None` for deleted files.
+    pub new_path: Option<String>,
+    pub status: String,
+    /// Rename or copy similarity percentage.
+    pub similarity: Option<u32>,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    pub is_binary: bool,
+    pub additions: u32,
+    pub deletions: u32,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A hunk of consecutive changes within a file.
+#[derive(Debug, Serialize)]
+pub struct DiffHunk {
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// Function or section context git prints after the `@@` range.
+    pub section: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// A single line of a hunk.
+#[derive(Debug, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    /// Set when git reports "\ No newline at end of file" for this line.
+    pub no_newline: bool,
+    /// Changed word ranges, only filled when word highlights are requested.
+    pub highlights: Vec<WordRange>,
+}
+
+/// Kind of a diff line.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// A changed range within a line, in character offsets (end exclusive).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WordRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Get a structured diff (staged or unstaged), optionally limited to paths.
+///
+/// `paths` are passed to git as pathspecs, so both files and directories work.
+/// With `word_diff`, paired removed/added lines carry intra-line highlights.
+#[tauri::command]
+pub async fn git_diff_files(
+    workspace_path: String,
+    staged: bool,
+    paths: Option<Vec<String>>,
+    word_diff: Option<bool>,
+) -> Result<Vec<DiffFile>, String> {
+    let mut args = vec![
+        "-c",
+        "core.quotepath=false",
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "-M",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ];
+    if staged {
+        args.push("--cached");
+    }
+    args.push("--");
+    if let Some(paths) = &paths {
+        args.extend(paths.iter().map(String::as_str));
+    }
+
+    let output = run_git(&workspace_path, &args).await?;
+    let mut files = parse_unified_diff(&output);
+    if word_diff.unwrap_or(false) {
+        for file in &mut files {
+            for hunk in &mut file.hunks {
+                add_word_highlights(hunk);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Parse `git diff` output into structured files and hunks.
+pub fn parse_unified_diff(output: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut old_remaining = 0u32;
+    let mut new_remaining = 0u32;
+    let mut old_line = 0u32;
+    let mut new_line = 0u32;
+    let mut skip_file = false;
+
+    for line in output.split_terminator('\n') {
+        // Hunk lines are consumed by count, so content starting with
+        // "---" or "diff" is never mistaken for a header.
+        if old_remaining > 0 || new_remaining > 0 {
+            let Some(file) = files.last_mut() else { break };
+            let Some(hunk) = file.hunks.last_mut() else {
+                break;
+            };
+            let (kind, content) = match line.chars().next() {
+                Some('+') => (DiffLineKind::Added, &line[1..]),
+                Some('-') => (DiffLineKind::Removed, &line[1..]),
+                Some('\\') => {
+                    if let Some(last) = hunk.lines.last_mut() {
+                        last.no_newline = true;
+                    }
+                    continue;
+                }
+                Some(c) => (DiffLineKind::Context, &line[c.len_utf8()..]),
+                None => (DiffLineKind::Context, ""),
+            };
+
+            let (old, new) = match kind {
+                DiffLineKind::Added => {
+                    new_remaining = new_remaining.saturating_sub(1);
+                    file.additions += 1;
+                    new_line += 1;
+                    (None, Some(new_line - 1))
+                }
+                DiffLineKind::Removed => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    file.deletions += 1;
+                    old_line += 1;
+                    (Some(old_line - 1), None)
+                }
+                DiffLineKind::Context => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                    old_line += 1;
+                    new_line += 1;
+                    (Some(old_line - 1), Some(new_line - 1))
+                }
+            };
+
+            hunk.lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+                old_line: old,
+                new_line: new,
+                no_newline: false,
+                highlights: Vec::new(),
+            });
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let (old_path, new_path) = parse_git_header_paths(rest);
+            files.push(new_diff_file(Some(old_path), Some(new_path), "modified"));
+            skip_file = false;
+            continue;
+        }
+
+        if let Some(rest) = line
+            .strip_prefix("diff --cc ")
+            .or_else(|| line.strip_prefix("diff --combined "))
+        {
+            // Combined diffs of unmerged paths have no two-sided hunks.
+            let path = unquote_path(rest);
+            files.push(new_diff_file(Some(path.clone()), Some(path), "unmerged"));
+            skip_file = true;
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if skip_file {
+            continue;
+        }
+
+        if let Some(mode) = line.strip_prefix("old mode ") {
+            file.old_mode = Some(mode.to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            file.new_mode = Some(mode.to_string());
+        } else if let Some(mode) = line.strip_prefix("deleted file mode ") {
+            file.status = "deleted".to_string();
+            file.old_mode = Some(mode.to_string());
+            file.new_path = None;
+        } else if let Some(mode) = line.strip_prefix("new file mode ") {
+            file.status = "added".to_string();
+            file.new_mode = Some(mode.to_string());
+            file.old_path = None;
+        } else if let Some(value) = line.strip_prefix("similarity index ") {
+            file.similarity = value.trim_end_matches('%').parse().ok();
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.status = "renamed".to_string();
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.status = "renamed".to_string();
+            file.new_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            file.status = "copied".to_string();
+            file.old_path = Some(unquote_path(path));
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            file.status = "copied".to_string();
+            file.new_path = Some(unquote_path(path));
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.is_binary = true;
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            file.old_path = strip_diff_prefix(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file.new_path = strip_diff_prefix(path, "b/");
+        } else if line.starts_with("@@ ") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                old_remaining = hunk.old_lines;
+                new_remaining = hunk.new_lines;
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                file.hunks.push(hunk);
+            }
+        }
+    }
+
+    files
+}
+
+fn new_diff_file(old_path: Option<String>, new_path: Option<String>, status: &str) -> DiffFile {
+    DiffFile {
+        old_path,
+        new_path,
+        status: status.to_string(),
+        similarity: None,
+        old_mode: None,
+        new_mode: None,
+        is_binary: false,
+        additions: 0,
+        deletions: 0,
+        hunks: Vec::new(),
+    }
+}
+
+/// Parse a hunk header such as `@@ -1,3 +1,4 @@ fn main()`.
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let inner = line.strip_prefix("@@ ")?;
+    let end = inner.find(" @@")?;
+    let mut ranges = inner[..end].split(' ');
+    let (old_start, old_lines) = parse_range(ranges.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = parse_range(ranges.next()?.strip_prefix('+')?)?;
+    let section = inner[end + 3..].trim_start().to_string();
+
+    Some(DiffHunk {
+        header: line.to_string(),
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        section,
+        lines: Vec::new(),
+    })
+}
+
+/// Parse `start[,count]`, where a missing count means one line.
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Best-effort paths from `diff --git a/<old> b/<new>`, refined later by
+/// the `---`/`+++` and rename lines when git prints them.
+fn parse_git_header_paths(rest: &str) -> (String, String) {
+    if rest.starts_with('"') {
+        if let Some((old, new)) = split_quoted_pair(rest) {
+            return (
+                old.strip_prefix("a/").unwrap_or(&old).to_string(),
+                new.strip_prefix("b/").unwrap_or(&new).to_string(),
+            );
+        }
+    }
+
+    // Unchanged paths make the header "a/P b/P", so P is exactly half of it.
+    if rest.len() > 5 && (rest.len() - 5).is_multiple_of(2) {
+        let len = (rest.len() - 5) / 2;
+        if rest.is_char_boundary(2 + len) && rest.is_char_boundary(len + 5) {
+            let old = &rest[2..2 + len];
+            let new = &rest[len + 5..];
+            if old == new && rest.starts_with("a/") && rest[2 + len..].starts_with(" b/") {
+                return (old.to_string(), new.to_string());
+            }
+        }
+    }
+
+    match rest.split_once(" b/") {
+        Some((old, new)) => (
+            old.strip_prefix("a/").unwrap_or(old).to_string(),
+            new.to_string(),
+        ),
+        None => (rest.to_string(), rest.to_string()),
+    }
+}
+
+/// Split two space-separated, possibly quoted, paths.
+fn split_quoted_pair(rest: &str) -> Option<(String, String)> {
+    let (first, remainder) = if rest.starts_with('"') {
+        let end = quoted_end(rest)?;
+        (&rest[..=end], rest[end + 1..].trim_start())
+    } else {
+        rest.split_once(' ')?
+    };
+    Some((unquote_path(first), unquote_path(remainder)))
+}
+
+/// Index of the closing quote of a C-style quoted string.
+fn quoted_end(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Strip the `a/`/`b/` prefix from a `---`/`+++` path; `/dev/null` yields `None`.
+fn strip_diff_prefix(path: &str, prefix: &str) -> Option<String> {
+    // Git appends a tab to these lines when the path contains a space.
+    let path = path.strip_suffix('\t').unwrap_or(path);
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = unquote_path(path);
+    Some(path.strip_prefix(prefix).unwrap_or(&path).to_string())
+}
+
+/// Undo git's C-style quoting of paths with special characters.
+pub fn unquote_path(path: &str) -> String {
+    let Some(inner) = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')) else {
+        return path.to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('r') => bytes.push(b'\r'),
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0c),
+            Some('v') => bytes.push(0x0b),
+            Some(d @ '0'..='7') => {
+                let mut value = d.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            value = value * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0u8; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Maximum token-matrix size for intra-line diffs; longer pairs are skipped.
+const MAX_WORD_DIFF_CELLS: usize = 250_000;
+
+/// Pair each run of removed lines with the added lines that follow it and
+/// mark the words that differ between each pair.
+pub fn add_word_highlights(hunk: &mut DiffHunk) {
+    let mut i = 0;
+    while i < hunk.lines.len() {
+        let removed_start = i;
+        while i < hunk.lines.len() && hunk.lines[i].kind == DiffLineKind::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < hunk.lines.len() && hunk.lines[i].kind == DiffLineKind::Added {
+            i += 1;
+        }
+
+        let pairs = (added_start - removed_start).min(i - added_start);
+        for offset in 0..pairs {
+            let (old, new) = word_diff(
+                &hunk.lines[removed_start + offset].content,
+                &hunk.lines[added_start + offset].content,
+            );
+            hunk.lines[removed_start + offset].highlights = old;
+            hunk.lines[added_start + offset].highlights = new;
+        }
+
+        if i == removed_start {
+            i += 1;
+        }
+    }
+}
+
+/// Compute changed ranges between two lines using an LCS over word tokens.
+pub fn word_diff(old: &str, new: &str) -> (Vec<WordRange>, Vec<WordRange>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let (n, m) = (old_tokens.len(), new_tokens.len());
+    if (n + 1) * (m + 1) > MAX_WORD_DIFF_CELLS {
+        return (Vec::new(), Vec::new());
+    }
+
+    // lcs[i][j] = LCS length of old_tokens[i..] and new_tokens[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_tokens[i].1 == new_tokens[j].1 {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_ranges = Vec::new();
+    let mut new_ranges = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_tokens[i].1 == new_tokens[j].1 {
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            push_range(&mut new_ranges, new_tokens[j].0, new_tokens[j].1);
+            j += 1;
+        } else {
+            push_range(&mut old_ranges, old_tokens[i].0, old_tokens[i].1);
+            i += 1;
+        }
+    }
+
+    (old_ranges, new_ranges)
+}
+
+/// Append a token's range, merging it with the previous range when adjacent.
+fn push_range(ranges: &mut Vec<WordRange>, start: usize, token: &str) {
+    let end = start + token.chars().count();
+    match ranges.last_mut() {
+        Some(last) if last.end == start => last.end = end,
+        _ => ranges.push(WordRange { start, end }),
+    }
+}
+
+/// Split a line into word, whitespace and punctuation tokens with their
+/// character offsets.
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start_byte = 0;
+    let mut start_char = 0;
+    let mut current: Option<u8> = None;
+
+    for (char_index, (byte_index, c)) in line.char_indices().enumerate() {
+        let class = if c.is_alphanumeric() || c == '_' {
+            Some(0)
+        } else if c.is_whitespace() {
+            Some(1)
+        } else {
+            None
+        };
+
+        let continues = class.is_some() && class == current;
+        if !continues && byte_index > 0 {
+            tokens.push((start_char, &line[start_byte..byte_index]));
+            start_byte = byte_index;
+            start_char = char_index;
+        }
+        current = class;
+    }
+    if start_byte < line.len() {
+        tokens.push((start_char, &line[start_byte..]));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{init_repo, repo_path, write};
+
+    #[test]
+    fn test_parse_unified_diff_modified_file() {
+        let output = "diff --git a/src/main.rs b/src/main.rs\nindex abc1234..def5678 100644\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@ fn main() {\n a\n-b\n+B\n c\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.status, "modified");
+        assert_eq!(file.old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(file.new_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(file.additions, 1);
+        assert_eq!(file.deletions, 1);
+
+        let hunk = &file.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines), (1, 3));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 3));
+        assert_eq!(hunk.section, "fn main() {");
+        let kinds: Vec<DiffLineKind> = hunk.lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::Context,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+                DiffLineKind::Context
+            ]
+        );
+        assert_eq!(hunk.lines[1].old_line, Some(2));
+        assert_eq!(hunk.lines[1].new_line, None);
+        assert_eq!(hunk.lines[2].old_line, None);
+        assert_eq!(hunk.lines[2].new_line, Some(2));
+        assert_eq!(hunk.lines[3].old_line, Some(3));
+        assert_eq!(hunk.lines[3].new_line, Some(3));
+    }
+
+    #[test]
+    fn test_parse_unified_diff_added_and_deleted() {
+        let output = "diff --git a/new.txt b/new.txt\nnew file mode 100644\nindex 0000000..abc1234\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hello\ndiff --git a/old.txt b/old.txt\ndeleted file mode 100644\nindex abc1234..0000000\n--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-bye\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files.len(), 2);
+
+        assert_eq!(files[0].status, "added");
+        assert_eq!(files[0].old_path, None);
+        assert_eq!(files[0].new_path.as_deref(), Some("new.txt"));
+        assert_eq!(files[0].new_mode.as_deref(), Some("100644"));
+        assert_eq!(files[0].hunks[0].lines[0].new_line, Some(1));
+
+        assert_eq!(files[1].status, "deleted");
+        assert_eq!(files[1].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(files[1].new_path, None);
+        assert_eq!(files[1].deletions, 1);
+    }
+
+    #[test]
+    fn test_parse_unified_diff_rename() {
+        let output = "diff --git a/old name.rs b/new name.rs\nsimilarity index 92%\nrename from old name.rs\nrename to new name.rs\nindex abc..def 100644\n--- a/old name.rs\t\n+++ b/new name.rs\t\n@@ -1 +1 @@\n-x\n+y\ndiff --git a/a.txt b/b.txt\nsimilarity index 100%\nrename from a.txt\nrename to b.txt\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].status, "renamed");
+        assert_eq!(files[0].similarity, Some(92));
+        assert_eq!(files[0].old_path.as_deref(), Some("old name.rs"));
+        assert_eq!(files[0].new_path.as_deref(), Some("new name.rs"));
+        assert_eq!(files[0].hunks.len(), 1);
+
+        assert_eq!(files[1].status, "renamed");
+        assert_eq!(files[1].old_path.as_deref(), Some("a.txt"));
+        assert_eq!(files[1].new_path.as_deref(), Some("b.txt"));
+        assert!(files[1].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_unified_diff_binary_and_mode_change() {
+        let output = "diff --git a/logo.png b/logo.png\nindex abc..def 100644\nBinary files a/logo.png and b/logo.png differ\ndiff --git a/run me.sh b/run me.sh\nold mode 100644\nnew mode 100755\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files.len(), 2);
+        assert!(files[0].is_binary);
+        assert_eq!(files[0].new_path.as_deref(), Some("logo.png"));
+        assert!(files[0].hunks.is_empty());
+
+        assert_eq!(files[1].old_path.as_deref(), Some("run me.sh"));
+        assert_eq!(files[1].new_path.as_deref(), Some("run me.sh"));
+        assert_eq!(files[1].old_mode.as_deref(), Some("100644"));
+        assert_eq!(files[1].new_mode.as_deref(), Some("100755"));
+        assert!(!files[1].is_binary);
+    }
+
+    #[test]
+    fn test_parse_unified_diff_header_like_content() {
+        // A removed line "-- x" renders as "--- x" and must stay in the hunk.
+        let output = "diff --git a/a.sql b/a.sql\n--- a/a.sql\n+++ b/a.sql\n@@ -1,2 +1,2 @@\n--- x\n+++ y\n-diff --git\n+z\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files.len(), 1);
+        let hunk = &files[0].hunks[0];
+        assert_eq!(hunk.lines.len(), 4);
+        assert_eq!(hunk.lines[0].content, "-- x");
+        assert_eq!(hunk.lines[1].content, "++ y");
+        assert_eq!(files[0].new_path.as_deref(), Some("a.sql"));
+    }
+
+    #[test]
+    fn test_parse_unified_diff_no_newline_marker() {
+        let output = "diff --git a/f b/f\n--- a/f\n+++ b/f\n@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+a\n";
+        let files = parse_unified_diff(output);
+        let lines = &files[0].hunks[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].no_newline);
+        assert!(!lines[1].no_newline);
+    }
+
+    #[test]
+    fn test_parse_unified_diff_quoted_paths() {
+        let output = "diff --git \"a/tab\\there.txt\" \"b/caf\\303\\251.txt\"\nsimilarity index 100%\nrename from \"tab\\there.txt\"\nrename to \"caf\\303\\251.txt\"\n";
+        let files = parse_unified_diff(output);
+        assert_eq!(files[0].old_path.as_deref(), Some("tab\there.txt"));
+        assert_eq!(files[0].new_path.as_deref(), Some("café.txt"));
+    }
+
+    #[test]
+    fn test_parse_unified_diff_empty() {
+        assert!(parse_unified_diff("").is_empty());
+    }
+
+    #[test]
+    fn test_word_diff_marks_changed_words() {
+        let (old, new) = word_diff("let count = 1;", "let total = 2;");
+        assert_eq!(
+            old,
+            vec![
+                WordRange { start: 4, end: 9 },
+                WordRange { start: 12, end: 13 }
+            ]
+        );
+        assert_eq!(
+            new,
+            vec![
+                WordRange { start: 4, end: 9 },
+                WordRange { start: 12, end: 13 }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_highlights_pair_removed_and_added() {
+        let output =
+            "diff --git a/f b/f\n--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n-foo bar\n+foo baz\n same\n";
+        let mut files = parse_unified_diff(output);
+        add_word_highlights(&mut files[0].hunks[0]);
+        let lines = &files[0].hunks[0].lines;
+        assert_eq!(lines[0].highlights, vec![WordRange { start: 4, end: 7 }]);
+        assert_eq!(lines[1].highlights, vec![WordRange { start: 4, end: 7 }]);
+        assert!(lines[2].highlights.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_git_diff_files_with_path_filter() {
+        let dir = init_repo(&[("src/a.rs", "one\n"), ("docs/b.md", "two\n")]);
+        write(&dir, "src/a.rs", "uno\n");
+        write(&dir, "docs/b.md", "dos\n");
+
+        let all = git_diff_files(repo_path(&dir), false, None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let filtered = git_diff_files(repo_path(&dir), false, Some(vec!["src".into()]), Some(true))
+            .await
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].new_path.as_deref(), Some("src/a.rs"));
+        assert_eq!(filtered[0].hunks[0].lines[0].highlights.len(), 1);
+    }
+}
@@ -1,151 +1,132 @@
 use serde::Serialize;
 use std::path::Path;
+use std::process::Stdio;
+use tauri::State;
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
+use super::config::{expand_home, find_workspace_config, DitLoopConfigFile, SigningConfig};
+use super::config_state::ConfigState;
+use super::diff::{parse_unified_diff, DiffFile};
+use super::identity::{guard_identity_with, IdentityError, IdentityMismatch, IdentityPolicy};
+use super::repo::{resolve_repo, RepoKind};
+use super::workspace_settings::protected_branch;
+
 /// Parsed git status output.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GitStatus {
     pub branch: String,
+    /// Upstream tracking branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
     pub ahead: u32,
     pub behind: u32,
     pub staged: Vec<FileChange>,
     pub unstaged: Vec<FileChange>,
     pub untracked: Vec<String>,
+    pub conflicted: Vec<ConflictEntry>,
+    pub ignored: Vec<String>,
 }
 
 /// A single file change entry.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileChange {
     pub path: String,
     pub status: String,
+    /// Source path of a rename or copy.
+    pub original_path: Option<String>,
+    /// Set when the entry is a submodule.
+    pub submodule: Option<SubmoduleState>,
 }
 
-/// A git commit entry.
-#[derive(Debug, Serialize)]
-pub struct GitCommit {
+/// State of a submodule entry in git status.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmoduleState {
+    pub commit_changed: bool,
+    pub has_tracked_changes: bool,
+    pub has_untracked_changes: bool,
+}
+
+/// An unmerged (conflicted) entry in git status.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictEntry {
+    pub path: String,
+    /// Porcelain XY code such as `UU` or `AA`.
+    pub code: String,
+    pub status: String,
+}
+
+/// A stash entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitStash {
+    /// Position in the stash list; `0` is the most recent.
+    pub index: usize,
+    /// Ref name such as `stash@{0}`.
+    pub reference: String,
     pub hash: String,
-    pub short_hash: String,
+    /// Branch the stash was made on.
+    pub branch: Option<String>,
     pub message: String,
-    pub author: String,
     pub date: String,
 }
 
-/// A git branch entry.
+/// Error returned by `git_commit`.
 #[derive(Debug, Serialize)]
-pub struct GitBranch {
-    pub name: String,
-    pub is_current: bool,
-    pub is_remote: bool,
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CommitError {
+    IdentityMismatch(IdentityMismatch),
+    /// The current branch is in the workspace's `protectedBranches`.
+    ProtectedBranch {
+        branch: String,
+    },
+    /// The configured gpg or ssh-keygen program could not be run.
+    SigningProgramMissing {
+        program: String,
+    },
+    /// The signing program ran but did not produce a signature.
+    SigningFailed {
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
 }
 
-/// Get parsed git status for a workspace.
-#[tauri::command]
-pub async fn git_status(workspace_path: String) -> Result<GitStatus, String> {
-    let path = Path::new(&workspace_path);
-    if !path.join(".git").exists() {
-        return Err("Not a git repository".to_string());
-    }
-
-    let output = Command::new("git")
-        .args(["status", "--porcelain=v2", "--branch"])
-        .current_dir(path)
-        .output()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut branch = String::new();
-    let mut ahead = 0u32;
-    let mut behind = 0u32;
-    let mut staged = Vec::new();
-    let mut unstaged = Vec::new();
-    let mut untracked = Vec::new();
-
-    for line in stdout.lines() {
-        if line.starts_with("# branch.head ") {
-            branch = line.strip_prefix("# branch.head ").unwrap_or("").to_string();
-        } else if line.starts_with("# branch.ab ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 4 {
-                ahead = parts[2].trim_start_matches('+').parse().unwrap_or(0);
-                behind = parts[3].trim_start_matches('-').parse().unwrap_or(0);
-            }
-        } else if line.starts_with("1 ") || line.starts_with("2 ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 9 {
-                let xy = parts[1];
-                let file_path = parts.last().unwrap_or(&"").to_string();
-                let x = xy.chars().next().unwrap_or('.');
-                let y = xy.chars().nth(1).unwrap_or('.');
-
-                if x != '.' {
-                    staged.push(FileChange {
-                        path: file_path.clone(),
-                        status: char_to_status(x),
-                    });
-                }
-                if y != '.' {
-                    unstaged.push(FileChange {
-                        path: file_path,
-                        status: char_to_status(y),
-                    });
-                }
-            }
-        } else if line.starts_with("? ") {
-            let file_path = line.strip_prefix("? ").unwrap_or("").to_string();
-            untracked.push(file_path);
+impl From<IdentityError> for CommitError {
+    fn from(error: IdentityError) -> Self {
+        match error {
+            IdentityError::Mismatch(mismatch) => CommitError::IdentityMismatch(mismatch),
+            IdentityError::Failed(message) => CommitError::Failed { message },
         }
     }
+}
 
-    Ok(GitStatus {
-        branch,
-        ahead,
-        behind,
-        staged,
-        unstaged,
-        untracked,
-    })
+impl From<String> for CommitError {
+    fn from(message: String) -> Self {
+        CommitError::Failed { message }
+    }
 }
 
-/// Get recent git log entries.
+/// Get parsed git status for a workspace.
+///
+/// Ignored files are only listed when `include_ignored` is set.
 #[tauri::command]
-pub async fn git_log(workspace_path: String, count: u32) -> Result<Vec<GitCommit>, String> {
-    let output = Command::new("git")
-        .args([
-            "log",
-            &format!("-{}", count),
-            "--format=%H%n%h%n%s%n%an%n%ai",
-        ])
-        .current_dir(&workspace_path)
-        .output()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+pub async fn git_status(
+    workspace_path: String,
+    include_ignored: Option<bool>,
+) -> Result<GitStatus, String> {
+    let path = Path::new(&workspace_path);
+    match resolve_repo(path).await {
+        Some(repo) if repo.kind != RepoKind::Bare => {}
+        _ => return Err("Not a git repository".to_string()),
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
-    let mut commits = Vec::new();
-
-    for chunk in lines.chunks(5) {
-        if chunk.len() >= 5 {
-            commits.push(GitCommit {
-                hash: chunk[0].to_string(),
-                short_hash: chunk[1].to_string(),
-                message: chunk[2].to_string(),
-                author: chunk[3].to_string(),
-                date: chunk[4].to_string(),
-            });
-        }
+    let mut args = vec!["status", "--porcelain=v2", "--branch", "-z"];
+    if include_ignored.unwrap_or(false) {
+        args.push("--ignored=matching");
     }
 
-    Ok(commits)
+    let output = run_git(&workspace_path, &args).await?;
+    Ok(parse_porcelain_v2(&output))
 }
 
 /// Get unified diff (staged or unstaged).
@@ -166,71 +147,610 @@ pub async fn git_diff(workspace_path: String, staged: bool) -> Result<String, St
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// List local and remote branches.
+/// Commit staged changes with a message.
+///
+/// Commits to a protected branch are refused. The workspace's profile
+/// identity is checked next; `identity_policy` can auto-fix the repository
+/// identity or skip the check for one commit. The profile's signing
+/// settings, if any, override the repository's.
 #[tauri::command]
-pub async fn git_branch_list(workspace_path: String) -> Result<Vec<GitBranch>, String> {
+pub async fn git_commit(
+    config_state: State<'_, ConfigState>,
+    workspace_path: String,
+    message: String,
+    identity_policy: Option<IdentityPolicy>,
+) -> Result<String, CommitError> {
+    // A broken config must not drop the protected branches and signing.
+    let config = config_state.config()?.unwrap_or_default();
+    if let Some(branch) = protected_branch(&config, &workspace_path).await {
+        return Err(CommitError::ProtectedBranch { branch });
+    }
+    guard_identity_with(
+        &config,
+        &workspace_path,
+        identity_policy.unwrap_or_default(),
+    )
+    .await?;
+
+    let signing = profile_signing(&config, &workspace_path);
+    commit_with_signing(&workspace_path, &message, signing).await
+}
+
+/// Signing settings of the workspace's profile, if it has any.
+pub(crate) fn profile_signing<'a>(
+    config: &'a DitLoopConfigFile,
+    workspace_path: &str,
+) -> Option<&'a SigningConfig> {
+    find_workspace_config(config, Path::new(workspace_path))
+        .and_then(|ws| config.profiles.get(&ws.profile))
+        .and_then(|profile| profile.signing.as_ref())
+}
+
+/// Run `git commit`, signing as configured.
+pub(crate) async fn commit_with_signing(
+    workspace_path: &str,
+    message: &str,
+    signing: Option<&SigningConfig>,
+) -> Result<String, CommitError> {
+    let mut args = signing.map(signing_args).unwrap_or_default();
+    args.extend(["commit".to_string(), "-m".to_string(), message.to_string()]);
+
     let output = Command::new("git")
-        .args(["branch", "-a", "--no-color"])
-        .current_dir(&workspace_path)
+        .args(&args)
+        .current_dir(workspace_path)
         .output()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| CommitError::Failed {
+            message: e.to_string(),
+        })?;
 
     if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let branches = stdout
-        .lines()
-        .map(|line| {
-            let is_current = line.starts_with("* ");
-            let name = line.trim_start_matches("* ").trim().to_string();
-            let is_remote = name.starts_with("remotes/");
-            GitBranch {
-                name: name.trim_start_matches("remotes/").to_string(),
-                is_current,
-                is_remote,
-            }
-        })
-        .filter(|b| !b.name.contains("->"))
+        let signs = matches!(
+            signing,
+            Some(SigningConfig::Gpg { .. } | SigningConfig::Ssh { .. })
+        );
+        return Err(classify_commit_error(
+            &String::from_utf8_lossy(&output.stderr),
+            signs,
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// `-c` overrides that make commits sign with the profile's key, or not
+/// sign at all. Commands that commit on their own, like `rebase
+/// --continue`, honor them too.
+pub(crate) fn signing_args(signing: &SigningConfig) -> Vec<String> {
+    let (format, key, program_key, program) = match signing {
+        SigningConfig::Gpg { key, program } => ("openpgp", key.clone(), "gpg.program", program),
+        SigningConfig::Ssh { key, program } => {
+            ("ssh", expand_home(key), "gpg.ssh.program", program)
+        }
+        SigningConfig::None => return vec!["-c".to_string(), "commit.gpgsign=false".to_string()],
+    };
+
+    let mut args = vec![
+        "-c".to_string(),
+        format!("gpg.format={}", format),
+        "-c".to_string(),
+        format!("user.signingkey={}", key),
+        "-c".to_string(),
+        "commit.gpgsign=true".to_string(),
+    ];
+    if let Some(program) = program {
+        args.extend(["-c".to_string(), format!("{}={}", program_key, program)]);
+    }
+    args
+}
+
+/// Turn signing failures in `git commit` stderr into specific errors.
+///
+/// `signs` marks commits the profile asked to sign, where a commit object
+/// that could not be written means the signature step failed.
+fn classify_commit_error(stderr: &str, signs: bool) -> CommitError {
+    let message = stderr.trim().to_string();
+    if let Some(program) = stderr.lines().find_map(|line| {
+        line.strip_prefix("fatal: cannot run ")
+            .and_then(|rest| rest.split(": ").next())
+    }) {
+        return CommitError::SigningProgramMissing {
+            program: program.to_string(),
+        };
+    }
+    if stderr.contains("failed to sign the data")
+        || (signs && stderr.contains("failed to write commit object"))
+    {
+        return CommitError::SigningFailed { message };
+    }
+    CommitError::Failed { message }
+}
+
+/// Stage whole paths (modified, deleted or untracked) into the index.
+#[tauri::command]
+pub async fn git_stage(workspace_path: String, paths: Vec<String>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    run_git(&workspace_path, &args).await?;
+    Ok(())
+}
+
+/// Remove whole paths from the index, keeping working tree changes.
+#[tauri::command]
+pub async fn git_unstage(workspace_path: String, paths: Vec<String>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    // Before the first commit there is no HEAD to restore the index from.
+    let mut args = if has_head(&workspace_path).await {
+        vec!["restore", "--staged", "--"]
+    } else {
+        vec!["rm", "--cached", "-r", "-q", "--"]
+    };
+    args.extend(paths.iter().map(String::as_str));
+    run_git(&workspace_path, &args).await?;
+    Ok(())
+}
+
+/// Discard working tree changes for whole paths.
+///
+/// Tracked files are restored from the index; untracked files are deleted.
+#[tauri::command]
+pub async fn git_discard(workspace_path: String, paths: Vec<String>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    // Untracked files under the paths, including inside directories.
+    let untracked =
+        list_files(&workspace_path, &["--others", "--exclude-standard"], &paths).await?;
+    if !untracked.is_empty() {
+        let mut args = vec!["clean", "-f", "-q", "--"];
+        args.extend(untracked.iter().map(String::as_str));
+        run_git(&workspace_path, &args).await?;
+    }
+
+    // Only paths covering a tracked file can be restored; a directory of
+    // untracked files would fail with "pathspec did not match".
+    let tracked_files = list_files(&workspace_path, &[], &paths).await?;
+    let tracked: Vec<&str> = paths
+        .iter()
+        .filter(|p| tracked_files.iter().any(|file| path_covers(p, file)))
+        .map(String::as_str)
         .collect();
+    if !tracked.is_empty() {
+        let mut args = vec!["restore", "--worktree", "--"];
+        args.extend(tracked);
+        run_git(&workspace_path, &args).await?;
+    }
 
-    Ok(branches)
+    Ok(())
 }
 
-/// Commit staged changes with a message.
+/// `git ls-files` for the given paths, with extra options.
+async fn list_files(
+    workspace_path: &str,
+    options: &[&str],
+    paths: &[String],
+) -> Result<Vec<String>, String> {
+    let mut args = vec!["ls-files", "-z"];
+    args.extend(options);
+    args.push("--");
+    args.extend(paths.iter().map(String::as_str));
+    Ok(run_git(workspace_path, &args)
+        .await?
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.to_string())
+        .collect())
+}
+
+/// Whether `file` is `path` or inside it.
+fn path_covers(path: &str, file: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    path.is_empty()
+        || path == "."
+        || file == path
+        || file
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Stage a single hunk, or selected lines of it, from the unstaged diff of a file.
+///
+/// `hunk_index` and `lines` index into `git_diff(workspace_path, false)` for
+/// `file_path`; `lines` counts the lines after the `@@` header, starting at 0.
+#[tauri::command]
+pub async fn git_stage_hunk(
+    workspace_path: String,
+    file_path: String,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    apply_hunk(
+        &workspace_path,
+        &file_path,
+        hunk_index,
+        lines,
+        HunkAction::Stage,
+    )
+    .await
+}
+
+/// Unstage a single hunk, or selected lines of it, from the staged diff of a file.
+#[tauri::command]
+pub async fn git_unstage_hunk(
+    workspace_path: String,
+    file_path: String,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    apply_hunk(
+        &workspace_path,
+        &file_path,
+        hunk_index,
+        lines,
+        HunkAction::Unstage,
+    )
+    .await
+}
+
+/// Discard a single hunk, or selected lines of it, from the working tree.
 #[tauri::command]
-pub async fn git_commit(workspace_path: String, message: String) -> Result<String, String> {
+pub async fn git_discard_hunk(
+    workspace_path: String,
+    file_path: String,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    apply_hunk(
+        &workspace_path,
+        &file_path,
+        hunk_index,
+        lines,
+        HunkAction::Discard,
+    )
+    .await
+}
+
+/// What to do with a hunk selected from a diff.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HunkAction {
+    Stage,
+    Unstage,
+    Discard,
+}
+
+impl HunkAction {
+    /// Whether the hunk comes from the staged (`--cached`) diff.
+    fn uses_cached_diff(self) -> bool {
+        self == HunkAction::Unstage
+    }
+
+    /// Whether the patch is applied in reverse.
+    fn reverse(self) -> bool {
+        self != HunkAction::Stage
+    }
+
+    /// Arguments for `git apply` reading the patch from stdin.
+    fn apply_args(self) -> &'static [&'static str] {
+        match self {
+            HunkAction::Stage => &["apply", "--cached", "--whitespace=nowarn", "-"],
+            HunkAction::Unstage => &["apply", "--cached", "--reverse", "--whitespace=nowarn", "-"],
+            HunkAction::Discard => &["apply", "--reverse", "--whitespace=nowarn", "-"],
+        }
+    }
+}
+
+/// Build a one-hunk patch for `file_path` and feed it to `git apply`.
+async fn apply_hunk(
+    workspace_path: &str,
+    file_path: &str,
+    hunk_index: usize,
+    lines: Option<Vec<usize>>,
+    action: HunkAction,
+) -> Result<(), String> {
+    let mut args = vec![
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ];
+    if action.uses_cached_diff() {
+        args.push("--cached");
+    }
+    args.extend(["--", file_path]);
+
+    let diff = run_git(workspace_path, &args).await?;
+    let (header, hunks) = split_patch(&diff);
+    let hunk = hunks
+        .get(hunk_index)
+        .ok_or_else(|| format!("Hunk {} not found in diff of {}", hunk_index, file_path))?;
+
+    let patch = build_hunk_patch(&header, hunk, lines.as_deref(), action.reverse())
+        .ok_or("No changed lines selected")?;
+
+    run_git_with_input(workspace_path, action.apply_args(), &patch).await?;
+    Ok(())
+}
+
+/// A hunk split out of `git diff` output, kept as raw lines so it can be
+/// re-emitted as a patch.
+#[derive(Debug)]
+struct RawHunk {
+    old_start: u32,
+    lines: Vec<String>,
+}
+
+/// Split a single-file unified diff into its file header and hunks.
+fn split_patch(diff: &str) -> (Vec<String>, Vec<RawHunk>) {
+    let mut header = Vec::new();
+    let mut hunks: Vec<RawHunk> = Vec::new();
+
+    // Split on '\n' only so CRLF content survives the round trip.
+    for line in diff.split_terminator('\n') {
+        if line.starts_with("@@") {
+            let old_start = line
+                .split_whitespace()
+                .nth(1)
+                .and_then(|range| range.trim_start_matches('-').split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+            hunks.push(RawHunk {
+                old_start,
+                lines: Vec::new(),
+            });
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.lines.push(line.to_string());
+        } else {
+            header.push(line.to_string());
+        }
+    }
+
+    (header, hunks)
+}
+
+/// Build a patch containing one hunk, keeping only the selected changed lines.
+///
+/// Unselected changes are neutralised so the patch still applies: when
+/// applying forward an unselected `-` becomes context and an unselected `+`
+/// is dropped; in reverse the roles swap. Returns `None` if nothing changes.
+fn build_hunk_patch(
+    header: &[String],
+    hunk: &RawHunk,
+    selected: Option<&[usize]>,
+    reverse: bool,
+) -> Option<String> {
+    let (keep_as_context, drop) = if reverse { ('+', '-') } else { ('-', '+') };
+    let mut body: Vec<String> = Vec::new();
+    let mut old_count = 0u32;
+    let mut new_count = 0u32;
+    let mut has_change = false;
+    let mut dropped_previous = false;
+
+    for (index, line) in hunk.lines.iter().enumerate() {
+        let kind = line.chars().next().unwrap_or(' ');
+        if kind == '\\' {
+            // "\ No newline at end of file" belongs to the line before it.
+            if !dropped_previous {
+                body.push(line.clone());
+            }
+            continue;
+        }
+
+        let is_selected = selected.is_none_or(|s| s.contains(&index));
+        let line = if kind == keep_as_context && !is_selected {
+            format!(" {}", &line[1..])
+        } else if kind == drop && !is_selected {
+            dropped_previous = true;
+            continue;
+        } else {
+            line.clone()
+        };
+
+        match line.chars().next().unwrap_or(' ') {
+            '+' => {
+                new_count += 1;
+                has_change = true;
+            }
+            '-' => {
+                old_count += 1;
+                has_change = true;
+            }
+            _ => {
+                old_count += 1;
+                new_count += 1;
+            }
+        }
+        dropped_previous = false;
+        body.push(line);
+    }
+
+    if !has_change {
+        return None;
+    }
+
+    let new_start = match (old_count, new_count) {
+        (0, _) => hunk.old_start + 1,
+        (_, 0) => hunk.old_start.saturating_sub(1),
+        _ => hunk.old_start,
+    };
+
+    let mut patch = header.join("\n");
+    patch.push('\n');
+    patch.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        hunk.old_start, old_count, new_start, new_count
+    ));
+    for line in body {
+        patch.push_str(&line);
+        patch.push('\n');
+    }
+    Some(patch)
+}
+
+/// List stash entries, most recent first.
+#[tauri::command]
+pub async fn git_stash_list(workspace_path: String) -> Result<Vec<GitStash>, String> {
+    let output = run_git(
+        &workspace_path,
+        &["stash", "list", "-z", "--format=%gd%x00%H%x00%ci%x00%gs"],
+    )
+    .await?;
+    Ok(parse_stash_list(&output))
+}
+
+/// Stash local changes, optionally limited to `paths`. Untracked files
+/// are left alone unless `include_untracked` is set.
+///
+/// Returns the new entry, or `None` when there was nothing to stash.
+#[tauri::command]
+pub async fn git_stash_push(
+    workspace_path: String,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+    paths: Option<Vec<String>>,
+) -> Result<Option<GitStash>, String> {
+    let before = stash_top(&workspace_path).await;
+
+    let mut args = vec!["stash", "push"];
+    if include_untracked.unwrap_or(false) {
+        args.push("--include-untracked");
+    }
+    if let Some(message) = &message {
+        args.extend(["-m", message.as_str()]);
+    }
+    args.push("--");
+    if let Some(paths) = &paths {
+        args.extend(paths.iter().map(String::as_str));
+    }
+    run_git(&workspace_path, &args).await?;
+
+    let after = git_stash_list(workspace_path).await?.into_iter().next();
+    Ok(after.filter(|stash| Some(&stash.hash) != before.as_ref()))
+}
+
+/// Apply a stash entry, keeping it in the list.
+#[tauri::command]
+pub async fn git_stash_apply(workspace_path: String, index: usize) -> Result<(), String> {
+    run_git(&workspace_path, &["stash", "apply", &stash_ref(index)])
+        .await
+        .map(|_| ())
+}
+
+/// Apply a stash entry and drop it. On conflicts the entry is kept.
+#[tauri::command]
+pub async fn git_stash_pop(workspace_path: String, index: usize) -> Result<(), String> {
+    run_git(&workspace_path, &["stash", "pop", &stash_ref(index)])
+        .await
+        .map(|_| ())
+}
+
+/// Delete a stash entry.
+#[tauri::command]
+pub async fn git_stash_drop(workspace_path: String, index: usize) -> Result<(), String> {
+    run_git(&workspace_path, &["stash", "drop", &stash_ref(index)])
+        .await
+        .map(|_| ())
+}
+
+/// Structured diff of a stash entry against its base, untracked files included.
+#[tauri::command]
+pub async fn git_stash_show(workspace_path: String, index: usize) -> Result<Vec<DiffFile>, String> {
+    let reference = stash_ref(index);
+    let output = run_git(
+        &workspace_path,
+        &[
+            "-c",
+            "core.quotepath=false",
+            "stash",
+            "show",
+            "-p",
+            "--include-untracked",
+            "--no-color",
+            "--no-ext-diff",
+            "-M",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            &reference,
+        ],
+    )
+    .await?;
+    Ok(parse_unified_diff(&output))
+}
+
+fn stash_ref(index: usize) -> String {
+    format!("stash@{{{}}}", index)
+}
+
+/// Hash of the most recent stash, if any.
+async fn stash_top(workspace_path: &str) -> Option<String> {
+    run_git(
+        workspace_path,
+        &["rev-parse", "--verify", "-q", "refs/stash"],
+    )
+    .await
+    .ok()
+    .map(|hash| hash.trim().to_string())
+}
+
+/// Whether the repository has at least one commit.
+async fn has_head(workspace_path: &str) -> bool {
+    run_git(workspace_path, &["rev-parse", "--verify", "-q", "HEAD"])
+        .await
+        .is_ok()
+}
+
+/// Run git in a workspace and return stdout, or stderr as the error.
+pub(crate) async fn run_git(workspace_path: &str, args: &[&str]) -> Result<String, String> {
     let output = Command::new("git")
-        .args(["commit", "-m", &message])
-        .current_dir(&workspace_path)
+        .args(args)
+        .current_dir(workspace_path)
         .output()
         .await
         .map_err(|e| e.to_string())?;
 
     if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
     }
 
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// Checkout a branch.
-#[tauri::command]
-pub async fn git_checkout(workspace_path: String, branch: String) -> Result<(), String> {
-    let output = Command::new("git")
-        .args(["checkout", &branch])
-        .current_dir(&workspace_path)
-        .output()
-        .await
+/// Run git in a workspace with `input` written to its stdin.
+pub(crate) async fn run_git_with_input(
+    workspace_path: &str,
+    args: &[&str],
+    input: &str,
+) -> Result<String, String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(workspace_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| e.to_string())?;
 
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
     if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
     }
 
-    Ok(())
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
 /// Convert porcelain v2 status character to human-readable status.
@@ -247,64 +767,161 @@ fn char_to_status(c: char) -> String {
     .to_string()
 }
 
-/// Parse git porcelain v2 output into GitStatus (extracted for testability).
+/// Describe the XY code of an unmerged entry.
+fn conflict_to_status(code: &str) -> String {
+    match code {
+        "DD" => "both deleted",
+        "AU" => "added by us",
+        "UD" => "deleted by them",
+        "UA" => "added by them",
+        "DU" => "deleted by us",
+        "AA" => "both added",
+        "UU" => "both modified",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+/// Parse the `<sub>` field of a porcelain v2 entry (`N...` or `S<c><m><u>`).
+fn parse_submodule_field(field: &str) -> Option<SubmoduleState> {
+    let flags: Vec<char> = field.strip_prefix('S')?.chars().collect();
+    if flags.len() != 3 {
+        return None;
+    }
+    Some(SubmoduleState {
+        commit_changed: flags[0] == 'C',
+        has_tracked_changes: flags[1] == 'M',
+        has_untracked_changes: flags[2] == 'U',
+    })
+}
+
+/// Parse `git status --porcelain=v2 --branch -z` output into GitStatus.
+///
+/// Records are NUL-terminated, so paths keep their spaces, and rename
+/// entries are followed by an extra record holding the original path.
 pub fn parse_porcelain_v2(output: &str) -> GitStatus {
-    let mut branch = String::new();
-    let mut ahead = 0u32;
-    let mut behind = 0u32;
-    let mut staged = Vec::new();
-    let mut unstaged = Vec::new();
-    let mut untracked = Vec::new();
-
-    for line in output.lines() {
-        if line.starts_with("# branch.head ") {
-            branch = line.strip_prefix("# branch.head ").unwrap_or("").to_string();
-        } else if line.starts_with("# branch.ab ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 4 {
-                ahead = parts[2].trim_start_matches('+').parse().unwrap_or(0);
-                behind = parts[3].trim_start_matches('-').parse().unwrap_or(0);
-            }
-        } else if line.starts_with("1 ") || line.starts_with("2 ") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 9 {
-                let xy = parts[1];
-                let file_path = parts.last().unwrap_or(&"").to_string();
-                let x = xy.chars().next().unwrap_or('.');
-                let y = xy.chars().nth(1).unwrap_or('.');
-
-                if x != '.' {
-                    staged.push(FileChange {
-                        path: file_path.clone(),
-                        status: char_to_status(x),
-                    });
-                }
-                if y != '.' {
-                    unstaged.push(FileChange {
-                        path: file_path,
-                        status: char_to_status(y),
-                    });
+    let mut status = GitStatus {
+        branch: String::new(),
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        staged: Vec::new(),
+        unstaged: Vec::new(),
+        untracked: Vec::new(),
+        conflicted: Vec::new(),
+        ignored: Vec::new(),
+    };
+
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            if let Some(head) = header.strip_prefix("branch.head ") {
+                status.branch = head.to_string();
+            } else if let Some(upstream) = header.strip_prefix("branch.upstream ") {
+                status.upstream = Some(upstream.to_string());
+            } else if let Some(ab) = header.strip_prefix("branch.ab ") {
+                let parts: Vec<&str> = ab.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    status.ahead = parts[0].trim_start_matches('+').parse().unwrap_or(0);
+                    status.behind = parts[1].trim_start_matches('-').parse().unwrap_or(0);
                 }
             }
-        } else if line.starts_with("? ") {
-            let file_path = line.strip_prefix("? ").unwrap_or("").to_string();
-            untracked.push(file_path);
+        } else if record.starts_with("1 ") || record.starts_with("2 ") {
+            let is_rename = record.starts_with('2');
+            let field_count = if is_rename { 10 } else { 9 };
+            let parts: Vec<&str> = record.splitn(field_count, ' ').collect();
+            if parts.len() < field_count {
+                continue;
+            }
+
+            let xy = parts[1];
+            let submodule = parse_submodule_field(parts[2]);
+            let file_path = parts[field_count - 1].to_string();
+            let original_path = if is_rename {
+                records.next().map(|p| p.to_string())
+            } else {
+                None
+            };
+            let x = xy.chars().next().unwrap_or('.');
+            let y = xy.chars().nth(1).unwrap_or('.');
+
+            if x != '.' {
+                status.staged.push(FileChange {
+                    path: file_path.clone(),
+                    status: char_to_status(x),
+                    original_path: original_path.clone().filter(|_| x == 'R' || x == 'C'),
+                    submodule: submodule.clone(),
+                });
+            }
+            if y != '.' {
+                status.unstaged.push(FileChange {
+                    path: file_path,
+                    status: char_to_status(y),
+                    original_path: original_path.filter(|_| y == 'R' || y == 'C'),
+                    submodule,
+                });
+            }
+        } else if record.starts_with("u ") {
+            let parts: Vec<&str> = record.splitn(11, ' ').collect();
+            if parts.len() < 11 {
+                continue;
+            }
+            status.conflicted.push(ConflictEntry {
+                path: parts[10].to_string(),
+                code: parts[1].to_string(),
+                status: conflict_to_status(parts[1]),
+            });
+        } else if let Some(file_path) = record.strip_prefix("? ") {
+            status.untracked.push(file_path.to_string());
+        } else if let Some(file_path) = record.strip_prefix("! ") {
+            status.ignored.push(file_path.to_string());
         }
     }
 
-    GitStatus {
-        branch,
-        ahead,
-        behind,
-        staged,
-        unstaged,
-        untracked,
-    }
+    status
+}
+
+/// Parse `git stash list -z --format=%gd%x00%H%x00%ci%x00%gs` output.
+///
+/// The reflog subject is `WIP on <branch>: <commit>` for stashes without a
+/// message and `On <branch>: <message>` otherwise.
+pub fn parse_stash_list(output: &str) -> Vec<GitStash> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks(4)
+        .filter(|chunk| chunk.len() == 4)
+        .enumerate()
+        .map(|(index, f)| {
+            let subject = f[3];
+            let (branch, message) = match subject
+                .strip_prefix("WIP on ")
+                .or_else(|| subject.strip_prefix("On "))
+                .and_then(|rest| rest.split_once(": "))
+            {
+                Some((branch, message)) => (Some(branch.to_string()), message.to_string()),
+                None => (None, subject.to_string()),
+            };
+            GitStash {
+                index,
+                reference: f[0].to_string(),
+                hash: f[1].to_string(),
+                branch,
+                message,
+                date: f[2].to_string(),
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::test_support::{git, init_repo, read, read_index, repo_path, write};
+
+    /// Turn newline-separated fixture records into `-z` output.
+    fn nul(records: &str) -> String {
+        records.replace('\n', "\0")
+    }
 
     #[test]
     fn test_char_to_status() {
@@ -319,16 +936,17 @@ mod tests {
 
     #[test]
     fn test_parse_porcelain_v2_branch_info() {
-        let output = "# branch.oid abc123\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +3 -1\n";
+        let output = &nul("# branch.oid abc123\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +3 -1\n");
         let status = parse_porcelain_v2(output);
         assert_eq!(status.branch, "main");
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
         assert_eq!(status.ahead, 3);
         assert_eq!(status.behind, 1);
     }
 
     #[test]
     fn test_parse_porcelain_v2_staged_and_unstaged() {
-        let output = "# branch.head feat/test\n1 M. N... 100644 100644 100644 abc123 def456 src/main.rs\n1 .M N... 100644 100644 100644 abc123 def456 src/lib.rs\n";
+        let output = &nul("# branch.head feat/test\n1 M. N... 100644 100644 100644 abc123 def456 src/main.rs\n1 .M N... 100644 100644 100644 abc123 def456 src/lib.rs\n");
         let status = parse_porcelain_v2(output);
         assert_eq!(status.staged.len(), 1);
         assert_eq!(status.staged[0].path, "src/main.rs");
@@ -340,7 +958,7 @@ mod tests {
 
     #[test]
     fn test_parse_porcelain_v2_untracked() {
-        let output = "# branch.head main\n? new_file.txt\n? another.rs\n";
+        let output = &nul("# branch.head main\n? new_file.txt\n? another.rs\n");
         let status = parse_porcelain_v2(output);
         assert_eq!(status.untracked, vec!["new_file.txt", "another.rs"]);
     }
@@ -358,16 +976,442 @@ mod tests {
 
     #[test]
     fn test_parse_porcelain_v2_detached_head() {
-        let output = "# branch.head (detached)\n# branch.oid abc123\n";
+        let output = &nul("# branch.head (detached)\n# branch.oid abc123\n");
         let status = parse_porcelain_v2(output);
         assert_eq!(status.branch, "(detached)");
     }
 
     #[test]
     fn test_parse_porcelain_v2_added_file() {
-        let output = "# branch.head main\n1 A. N... 000000 100644 100644 0000000 abc1234 new_file.ts\n";
+        let output = &nul(
+            "# branch.head main\n1 A. N... 000000 100644 100644 0000000 abc1234 new_file.ts\n",
+        );
         let status = parse_porcelain_v2(output);
         assert_eq!(status.staged.len(), 1);
         assert_eq!(status.staged[0].status, "added");
     }
+
+    #[test]
+    fn test_parse_porcelain_v2_paths_with_spaces() {
+        let output = &nul("# branch.head main\n1 .M N... 100644 100644 100644 abc123 abc123 docs/my notes.md\n? new dir/a b.txt\n");
+        let status = parse_porcelain_v2(output);
+        assert_eq!(status.unstaged[0].path, "docs/my notes.md");
+        assert_eq!(status.untracked, vec!["new dir/a b.txt"]);
+        assert_eq!(status.upstream, None);
+    }
+
+    #[test]
+    fn test_parse_porcelain_v2_rename_keeps_original_path() {
+        let output = &nul("# branch.head main\n2 R. N... 100644 100644 100644 abc123 abc123 R100 src/new name.rs\nsrc/old name.rs\n1 .M N... 100644 100644 100644 abc123 abc123 other.rs\n");
+        let status = parse_porcelain_v2(output);
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].status, "renamed");
+        assert_eq!(status.staged[0].path, "src/new name.rs");
+        assert_eq!(
+            status.staged[0].original_path.as_deref(),
+            Some("src/old name.rs")
+        );
+        assert_eq!(status.unstaged.len(), 1);
+        assert_eq!(status.unstaged[0].path, "other.rs");
+        assert_eq!(status.unstaged[0].original_path, None);
+    }
+
+    #[test]
+    fn test_parse_porcelain_v2_unmerged_entries() {
+        let output = &nul("# branch.head main\nu UU N... 100644 100644 100644 100644 aaa bbb ccc src/both.rs\nu AU N... 000000 100644 000000 100644 000 bbb 000 new file.rs\n");
+        let status = parse_porcelain_v2(output);
+        assert!(status.staged.is_empty());
+        assert_eq!(status.conflicted.len(), 2);
+        assert_eq!(status.conflicted[0].path, "src/both.rs");
+        assert_eq!(status.conflicted[0].code, "UU");
+        assert_eq!(status.conflicted[0].status, "both modified");
+        assert_eq!(status.conflicted[1].path, "new file.rs");
+        assert_eq!(status.conflicted[1].status, "added by us");
+    }
+
+    #[test]
+    fn test_parse_porcelain_v2_submodule_and_ignored() {
+        let output = &nul("# branch.head main\n1 .M SC.U 160000 160000 160000 abc123 abc123 vendor/lib\n! target/\n");
+        let status = parse_porcelain_v2(output);
+        let submodule = status.unstaged[0].submodule.as_ref().unwrap();
+        assert!(submodule.commit_changed);
+        assert!(!submodule.has_tracked_changes);
+        assert!(submodule.has_untracked_changes);
+        assert_eq!(status.ignored, vec!["target/"]);
+    }
+
+    #[tokio::test]
+    async fn test_git_status_reports_renames_and_ignored() {
+        let dir = init_repo(&[("old name.txt", "content\n"), (".gitignore", "*.log\n")]);
+        let path = repo_path(&dir);
+        std::fs::rename(
+            dir.path().join("old name.txt"),
+            dir.path().join("new name.txt"),
+        )
+        .unwrap();
+        write(&dir, "debug.log", "noise\n");
+        git_stage(
+            path.clone(),
+            vec!["old name.txt".into(), "new name.txt".into()],
+        )
+        .await
+        .unwrap();
+
+        let status = git_status(path.clone(), None).await.unwrap();
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].path, "new name.txt");
+        assert_eq!(
+            status.staged[0].original_path.as_deref(),
+            Some("old name.txt")
+        );
+        assert!(status.ignored.is_empty());
+
+        let status = git_status(path, Some(true)).await.unwrap();
+        assert_eq!(status.ignored, vec!["debug.log"]);
+    }
+
+    const TEN_LINES: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+
+    #[test]
+    fn test_split_patch_header_and_hunks() {
+        let diff = "diff --git a/f b/f\nindex 1..2 100644\n--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n-a\n+b\n c\n@@ -10 +10 @@\n-x\n+y\n";
+        let (header, hunks) = split_patch(diff);
+        assert_eq!(header.len(), 4);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(hunks[0].lines, vec!["-a", "+b", " c"]);
+        assert_eq!(hunks[1].old_start, 10);
+    }
+
+    #[test]
+    fn test_build_hunk_patch_selected_lines_forward() {
+        let header = vec!["--- a/f".to_string(), "+++ b/f".to_string()];
+        let hunk = RawHunk {
+            old_start: 1,
+            lines: vec!["-a".into(), "+b".into(), "+c".into(), " d".into()],
+        };
+        // Keep only "+c": the removal of "a" becomes context, "+b" is dropped.
+        let patch = build_hunk_patch(&header, &hunk, Some(&[2]), false).unwrap();
+        assert_eq!(patch, "--- a/f\n+++ b/f\n@@ -1,2 +1,3 @@\n a\n+c\n d\n");
+    }
+
+    #[test]
+    fn test_build_hunk_patch_selected_lines_reverse() {
+        let header = vec!["--- a/f".to_string(), "+++ b/f".to_string()];
+        let hunk = RawHunk {
+            old_start: 1,
+            lines: vec!["-a".into(), "+b".into(), "+c".into(), " d".into()],
+        };
+        // Reverting only "+c": "+b" stays as context, "-a" is dropped.
+        let patch = build_hunk_patch(&header, &hunk, Some(&[2]), true).unwrap();
+        assert_eq!(patch, "--- a/f\n+++ b/f\n@@ -1,2 +1,3 @@\n b\n+c\n d\n");
+    }
+
+    #[test]
+    fn test_build_hunk_patch_nothing_selected() {
+        let hunk = RawHunk {
+            old_start: 1,
+            lines: vec!["-a".into(), "+b".into()],
+        };
+        assert!(build_hunk_patch(&[], &hunk, Some(&[]), false).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stage_and_unstage_paths() {
+        let dir = init_repo(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "a.txt", "changed\n");
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        write(&dir, "new file.txt", "new\n");
+
+        git_stage(
+            path.clone(),
+            vec!["a.txt".into(), "b.txt".into(), "new file.txt".into()],
+        )
+        .await
+        .unwrap();
+        let status = git_status(path.clone(), None).await.unwrap();
+        assert_eq!(status.staged.len(), 3);
+        assert!(status.unstaged.is_empty());
+        assert!(status.untracked.is_empty());
+
+        git_unstage(path.clone(), vec!["a.txt".into(), "b.txt".into()])
+            .await
+            .unwrap();
+        let status = git_status(path, None).await.unwrap();
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].status, "added");
+        assert_eq!(status.unstaged.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unstage_before_first_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        write(&dir, "a.txt", "a\n");
+        let path = repo_path(&dir);
+
+        git_stage(path.clone(), vec!["a.txt".into()]).await.unwrap();
+        git_unstage(path.clone(), vec!["a.txt".into()])
+            .await
+            .unwrap();
+        let status = git_status(path, None).await.unwrap();
+        assert!(status.staged.is_empty());
+        assert_eq!(status.untracked, vec!["a.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_discard_tracked_and_untracked() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "a.txt", "changed\n");
+        write(&dir, "scratch.txt", "tmp\n");
+
+        git_discard(path.clone(), vec!["a.txt".into(), "scratch.txt".into()])
+            .await
+            .unwrap();
+        assert_eq!(read(&dir, "a.txt"), "a\n");
+        assert!(!dir.path().join("scratch.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_discard_untracked_directory() {
+        let dir = init_repo(&[("a.txt", "a\n"), ("src/lib.rs", "lib\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "new/one.txt", "1\n");
+        write(&dir, "src/extra.rs", "extra\n");
+        write(&dir, "src/lib.rs", "changed\n");
+
+        git_discard(path, vec!["new".into(), "src/".into()])
+            .await
+            .unwrap();
+        assert!(!dir.path().join("new/one.txt").exists());
+        assert!(!dir.path().join("src/extra.rs").exists());
+        assert_eq!(read(&dir, "src/lib.rs"), "lib\n");
+    }
+
+    #[tokio::test]
+    async fn test_stage_and_unstage_single_hunk() {
+        let dir = init_repo(&[("f.txt", TEN_LINES)]);
+        let path = repo_path(&dir);
+        write(&dir, "f.txt", "one\n2\n3\n4\n5\n6\n7\n8\n9\nten\n");
+
+        git_stage_hunk(path.clone(), "f.txt".into(), 1, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            read_index(&dir, "f.txt"),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n"
+        );
+
+        git_unstage_hunk(path.clone(), "f.txt".into(), 0, None)
+            .await
+            .unwrap();
+        assert_eq!(read_index(&dir, "f.txt"), TEN_LINES);
+        assert_eq!(read(&dir, "f.txt"), "one\n2\n3\n4\n5\n6\n7\n8\n9\nten\n");
+    }
+
+    #[tokio::test]
+    async fn test_stage_selected_lines() {
+        let dir = init_repo(&[("f.txt", "a\nb\nc\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "f.txt", "a\nB\nc\nd\n");
+
+        // Hunk lines: " a", "-b", "+B", " c", "+d"; stage only "+d".
+        git_stage_hunk(path.clone(), "f.txt".into(), 0, Some(vec![4]))
+            .await
+            .unwrap();
+        assert_eq!(read_index(&dir, "f.txt"), "a\nb\nc\nd\n");
+
+        // Staging nothing is rejected.
+        assert!(
+            git_stage_hunk(path.clone(), "f.txt".into(), 0, Some(vec![0]))
+                .await
+                .is_err()
+        );
+        assert!(git_stage_hunk(path, "f.txt".into(), 5, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discard_selected_lines() {
+        let dir = init_repo(&[("f.txt", "a\nb\nc\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "f.txt", "a\nB\nc\nd\n");
+
+        // Discard the "b" -> "B" change and keep the appended "d".
+        git_discard_hunk(path, "f.txt".into(), 0, Some(vec![1, 2]))
+            .await
+            .unwrap();
+        assert_eq!(read(&dir, "f.txt"), "a\nb\nc\nd\n");
+        assert_eq!(read_index(&dir, "f.txt"), "a\nb\nc\n");
+    }
+
+    #[tokio::test]
+    async fn test_commit_signs_with_ssh_key_and_log_verifies() {
+        use crate::commands::log::{git_log, SignatureStatus};
+
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let keys = tempfile::tempdir().unwrap();
+        let key = keys.path().join("id_sign");
+        let key_path = key.to_string_lossy().to_string();
+        let status = std::process::Command::new("ssh-keygen")
+            .args([
+                "-q",
+                "-t",
+                "ed25519",
+                "-N",
+                "",
+                "-C",
+                "test@example.com",
+                "-f",
+                &key_path,
+            ])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let public_key = std::fs::read_to_string(format!("{}.pub", key_path)).unwrap();
+        let allowed = keys.path().join("allowed_signers");
+        std::fs::write(&allowed, format!("test@example.com {}", public_key)).unwrap();
+        git(
+            dir.path(),
+            &[
+                "config",
+                "gpg.ssh.allowedSignersFile",
+                &allowed.to_string_lossy(),
+            ],
+        );
+
+        write(&dir, "a.txt", "b\n");
+        git(dir.path(), &["add", "a.txt"]);
+        let signing = SigningConfig::Ssh {
+            key: key_path,
+            program: None,
+        };
+        commit_with_signing(&repo_path(&dir), "signed", Some(&signing))
+            .await
+            .unwrap();
+
+        let commits = git_log(repo_path(&dir), 2, None, None)
+            .await
+            .unwrap()
+            .commits;
+        assert_eq!(commits[0].signature, SignatureStatus::Good);
+        assert_eq!(commits[0].signer.as_deref(), Some("test@example.com"));
+        assert_eq!(commits[1].signature, SignatureStatus::Unsigned);
+        assert_eq!(commits[1].signer, None);
+    }
+
+    #[tokio::test]
+    async fn test_commit_signing_none_overrides_repository() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        git(dir.path(), &["config", "commit.gpgsign", "true"]);
+        git(dir.path(), &["config", "gpg.program", "/nonexistent/gpg"]);
+        write(&dir, "a.txt", "b\n");
+        git(dir.path(), &["add", "a.txt"]);
+
+        commit_with_signing(&repo_path(&dir), "unsigned", Some(&SigningConfig::None))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commit_reports_missing_signing_program() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        write(&dir, "a.txt", "b\n");
+        git(dir.path(), &["add", "a.txt"]);
+        let signing = SigningConfig::Gpg {
+            key: "3AA5C34371567BD2".to_string(),
+            program: Some("/nonexistent/gpg".to_string()),
+        };
+
+        let err = commit_with_signing(&repo_path(&dir), "signed", Some(&signing))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, CommitError::SigningProgramMissing { program } if program == "/nonexistent/gpg"),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = nul(
+            "stash@{0}\nabc\n2026-01-02 10:00:00 +0000\nWIP on main: 1234567 initial\nstash@{1}\ndef\n2026-01-01 10:00:00 +0000\nOn feature/x: before: refactor\n",
+        );
+        let stashes = parse_stash_list(&output);
+        assert_eq!(stashes.len(), 2);
+        assert_eq!(stashes[0].reference, "stash@{0}");
+        assert_eq!(stashes[0].branch.as_deref(), Some("main"));
+        assert_eq!(stashes[0].message, "1234567 initial");
+        assert_eq!(stashes[1].index, 1);
+        assert_eq!(stashes[1].branch.as_deref(), Some("feature/x"));
+        assert_eq!(stashes[1].message, "before: refactor");
+    }
+
+    #[tokio::test]
+    async fn test_stash_push_selected_paths_and_pop() {
+        let dir = init_repo(&[("a.txt", "a\n"), ("b.txt", "b\n")]);
+        let path = repo_path(&dir);
+        write(&dir, "a.txt", "a2\n");
+        write(&dir, "b.txt", "b2\n");
+
+        let stash = git_stash_push(
+            path.clone(),
+            Some("park a".into()),
+            None,
+            Some(vec!["a.txt".into()]),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(stash.message, "park a");
+        assert_eq!(stash.branch.as_deref(), Some("main"));
+        assert_eq!(read(&dir, "a.txt"), "a\n");
+        assert_eq!(read(&dir, "b.txt"), "b2\n");
+
+        let files = git_stash_show(path.clone(), 0).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_path.as_deref(), Some("a.txt"));
+
+        git_stash_pop(path.clone(), 0).await.unwrap();
+        assert_eq!(read(&dir, "a.txt"), "a2\n");
+        assert!(git_stash_list(path).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stash_untracked_apply_and_drop() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+
+        // Nothing to stash yet.
+        assert_eq!(
+            git_stash_push(path.clone(), None, Some(true), None)
+                .await
+                .unwrap(),
+            None
+        );
+
+        write(&dir, "new.txt", "new\n");
+        git_stash_push(path.clone(), None, Some(true), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!dir.path().join("new.txt").exists());
+
+        let files = git_stash_show(path.clone(), 0).await.unwrap();
+        assert_eq!(files[0].new_path.as_deref(), Some("new.txt"));
+
+        git_stash_apply(path.clone(), 0).await.unwrap();
+        assert_eq!(read(&dir, "new.txt"), "new\n");
+        assert_eq!(git_stash_list(path.clone()).await.unwrap().len(), 1);
+
+        git_stash_drop(path.clone(), 0).await.unwrap();
+        assert!(git_stash_list(path.clone()).await.unwrap().is_empty());
+        assert!(git_stash_drop(path, 0).await.is_err());
+    }
 }
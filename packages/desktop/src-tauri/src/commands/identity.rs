@@ -0,0 +1,539 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::config::{find_workspace_config, DitLoopConfigFile};
+use super::config_state::ConfigState;
+use super::git::run_git;
+use super::merge::resolve_git_dir;
+use super::workspace_settings::{email_in_domain, resolve_settings};
+
+/// A git author identity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitIdentity {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Result of comparing a workspace's profile with its effective git identity.
+#[derive(Debug, Serialize)]
+pub struct IdentityCheck {
+    /// Profile the workspace is configured with, if any.
+    pub profile: Option<String>,
+    pub expected: Option<GitIdentity>,
+    pub actual: GitIdentity,
+    pub matches: bool,
+    /// Email domain the workspace's settings expect, if any.
+    pub expected_domain: Option<String>,
+    /// The actual email is in the expected domain; true when none is set.
+    pub domain_matches: bool,
+}
+
+/// Config level an identity value was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigScope {
+    System,
+    Global,
+    Local,
+    Worktree,
+    /// A file pulled in by `include.path` or `includeIf` from another level.
+    Include,
+    /// `git -c` or `GIT_CONFIG_*` environment overrides.
+    Command,
+}
+
+/// An effective config value and where it came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigValueSource {
+    pub value: String,
+    pub scope: ConfigScope,
+    /// File the value was read from, or the raw origin for non-file sources.
+    pub origin: String,
+}
+
+/// Where the workspace's effective user.name and user.email are set.
+#[derive(Debug, Serialize)]
+pub struct IdentitySource {
+    pub name: Option<ConfigValueSource>,
+    pub email: Option<ConfigValueSource>,
+}
+
+/// Details of a commit or push refused because of the wrong identity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdentityMismatch {
+    /// Profile the workspace is configured with; empty when only the email
+    /// domain is checked.
+    pub profile: String,
+    pub expected_name: String,
+    pub expected_email: String,
+    /// Email domain the workspace's settings require, if any.
+    pub expected_domain: Option<String>,
+    pub actual_name: Option<String>,
+    pub actual_email: Option<String>,
+}
+
+/// How commits and pushes react to an identity that doesn't match the profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityPolicy {
+    /// Refuse with an identity mismatch error.
+    #[default]
+    Enforce,
+    /// Write the profile identity into the repository's local config first.
+    AutoFix,
+    /// Skip the check for a one-off commit or push.
+    Override,
+}
+
+/// Failure of the identity guard.
+#[derive(Debug, PartialEq)]
+pub enum IdentityError {
+    Mismatch(IdentityMismatch),
+    Failed(String),
+}
+
+impl From<String> for IdentityError {
+    fn from(message: String) -> Self {
+        IdentityError::Failed(message)
+    }
+}
+
+/// Compare the workspace's configured profile with its effective git identity.
+#[tauri::command]
+pub async fn git_check_identity(
+    config_state: State<'_, ConfigState>,
+    workspace_path: String,
+) -> Result<IdentityCheck, String> {
+    let config = config_state.config()?.unwrap_or_default();
+    check_identity(&config, &workspace_path).await
+}
+
+/// Report which config scope the workspace's effective identity comes from.
+#[tauri::command]
+pub async fn git_identity_source(workspace_path: String) -> Result<IdentitySource, String> {
+    Ok(IdentitySource {
+        name: config_value_source(&workspace_path, "user.name").await?,
+        email: config_value_source(&workspace_path, "user.email").await?,
+    })
+}
+
+/// Resolve the expected identity for a workspace and compare it with git's.
+///
+/// Emails are compared case-insensitively; the name is reported but not
+/// enforced. Workspaces without a configured profile always match. The
+/// email domain from the workspace's settings is checked separately.
+pub async fn check_identity(
+    config: &DitLoopConfigFile,
+    workspace_path: &str,
+) -> Result<IdentityCheck, String> {
+    let actual = effective_identity(workspace_path).await;
+    let expected_domain = resolve_settings(config, Path::new(workspace_path))
+        .email_domain
+        .map(|domain| domain.value);
+    let domain_matches = match (&expected_domain, &actual.email) {
+        (None, _) => true,
+        (Some(domain), Some(email)) => email_in_domain(email, domain),
+        (Some(_), None) => false,
+    };
+    let Some(workspace) = find_workspace_config(config, Path::new(workspace_path))
+        .filter(|ws| !ws.profile.is_empty())
+    else {
+        return Ok(IdentityCheck {
+            profile: None,
+            expected: None,
+            actual,
+            matches: true,
+            expected_domain,
+            domain_matches,
+        });
+    };
+
+    let profile = config.profiles.get(&workspace.profile).ok_or_else(|| {
+        format!(
+            "Profile '{}' used by workspace '{}' not found in config",
+            workspace.profile, workspace.name
+        )
+    })?;
+
+    let matches = actual
+        .email
+        .as_deref()
+        .is_some_and(|email| email.eq_ignore_ascii_case(&profile.email));
+
+    Ok(IdentityCheck {
+        profile: Some(workspace.profile.clone()),
+        expected: Some(GitIdentity {
+            name: Some(profile.name.clone()),
+            email: Some(profile.email.clone()),
+        }),
+        actual,
+        matches,
+        expected_domain,
+        domain_matches,
+    })
+}
+
+/// Enforce the identity policy before a commit or push: the email has to
+/// match the workspace's profile and be in its `emailDomain`, if set.
+pub async fn guard_identity(
+    config_state: &ConfigState,
+    workspace_path: &str,
+    policy: IdentityPolicy,
+) -> Result<(), IdentityError> {
+    if policy == IdentityPolicy::Override {
+        return Ok(());
+    }
+    let config = config_state.config()?.unwrap_or_default();
+    guard_identity_with(&config, workspace_path, policy).await
+}
+
+/// [`guard_identity`] against an already loaded config.
+pub async fn guard_identity_with(
+    config: &DitLoopConfigFile,
+    workspace_path: &str,
+    policy: IdentityPolicy,
+) -> Result<(), IdentityError> {
+    if policy == IdentityPolicy::Override {
+        return Ok(());
+    }
+
+    let check = check_identity(config, workspace_path).await?;
+    if check.matches && check.domain_matches {
+        return Ok(());
+    }
+
+    // Auto-fixing writes the profile's identity, so it needs a profile
+    // whose email is in the expected domain.
+    let fixable = check.expected.as_ref().is_some_and(|expected| {
+        let email = expected.email.as_deref().unwrap_or_default();
+        check
+            .expected_domain
+            .as_deref()
+            .is_none_or(|domain| email_in_domain(email, domain))
+    });
+    let expected = check.expected.unwrap_or(GitIdentity {
+        name: None,
+        email: None,
+    });
+    let expected_name = expected.name.unwrap_or_default();
+    let expected_email = expected.email.unwrap_or_default();
+
+    if policy == IdentityPolicy::AutoFix && fixable {
+        run_git(
+            workspace_path,
+            &["config", "--local", "user.name", &expected_name],
+        )
+        .await?;
+        run_git(
+            workspace_path,
+            &["config", "--local", "user.email", &expected_email],
+        )
+        .await?;
+        return Ok(());
+    }
+
+    Err(IdentityError::Mismatch(IdentityMismatch {
+        profile: check.profile.unwrap_or_default(),
+        expected_name,
+        expected_email,
+        expected_domain: check.expected_domain,
+        actual_name: check.actual.name,
+        actual_email: check.actual.email,
+    }))
+}
+
+/// The identity git would use for a commit in this workspace.
+pub async fn effective_identity(workspace_path: &str) -> GitIdentity {
+    GitIdentity {
+        name: read_git_config(workspace_path, "user.name").await,
+        email: read_git_config(workspace_path, "user.email").await,
+    }
+}
+
+/// Read a git config value as seen from the workspace, if set.
+async fn read_git_config(workspace_path: &str, key: &str) -> Option<String> {
+    run_git(workspace_path, &["config", "--get", key])
+        .await
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Look up a config value together with its scope and origin file.
+async fn config_value_source(
+    workspace_path: &str,
+    key: &str,
+) -> Result<Option<ConfigValueSource>, String> {
+    // A missing key exits non-zero without output, so treat failure as unset.
+    let Ok(output) = run_git(
+        workspace_path,
+        &[
+            "config",
+            "--show-origin",
+            "--show-scope",
+            "-z",
+            "--get",
+            key,
+        ],
+    )
+    .await
+    else {
+        return Ok(None);
+    };
+
+    let mut fields = output.split('\0');
+    let (Some(scope), Some(origin), Some(value)) = (fields.next(), fields.next(), fields.next())
+    else {
+        return Ok(None);
+    };
+
+    let scope = match origin.strip_prefix("file:") {
+        Some(file) => {
+            let primary = primary_config_files(workspace_path, scope).await;
+            let file = resolve_origin_file(workspace_path, file).await;
+            classify_file_scope(scope, &file, &primary)
+        }
+        None => ConfigScope::Command,
+    };
+
+    Ok(Some(ConfigValueSource {
+        value: value.to_string(),
+        scope,
+        origin: origin.strip_prefix("file:").unwrap_or(origin).to_string(),
+    }))
+}
+
+/// Map git's `--show-scope` output to a [`ConfigScope`], reporting values
+/// from any file other than the scope's own config files as included.
+fn classify_file_scope(scope: &str, file: &Path, primary: &[PathBuf]) -> ConfigScope {
+    let level = match scope {
+        "system" => ConfigScope::System,
+        "global" => ConfigScope::Global,
+        "local" => ConfigScope::Local,
+        "worktree" => ConfigScope::Worktree,
+        _ => ConfigScope::Command,
+    };
+    let file = canonical(file);
+    if level == ConfigScope::Command || primary.iter().any(|p| canonical(p) == file) {
+        level
+    } else {
+        ConfigScope::Include
+    }
+}
+
+/// The files git reads directly for a scope, before following includes.
+async fn primary_config_files(workspace_path: &str, scope: &str) -> Vec<PathBuf> {
+    match scope {
+        "system" => vec![std::env::var_os("GIT_CONFIG_SYSTEM")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/etc/gitconfig"))],
+        "global" => {
+            if let Some(path) = std::env::var_os("GIT_CONFIG_GLOBAL") {
+                return vec![PathBuf::from(path)];
+            }
+            let Some(home) = dirs::home_dir() else {
+                return Vec::new();
+            };
+            let xdg = std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".config"));
+            vec![home.join(".gitconfig"), xdg.join("git").join("config")]
+        }
+        "local" | "worktree" => match resolve_git_dir(workspace_path).await {
+            Ok(git_dir) => vec![git_dir.join("config"), git_dir.join("config.worktree")],
+            Err(_) => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Make a `--show-origin` path absolute. Relative paths are relative to the
+/// top of the working tree, or to the git dir in a bare repository.
+async fn resolve_origin_file(workspace_path: &str, file: &str) -> PathBuf {
+    let path = PathBuf::from(file);
+    if path.is_absolute() {
+        return path;
+    }
+    let base = match run_git(workspace_path, &["rev-parse", "--show-toplevel"]).await {
+        Ok(top) if !top.trim().is_empty() => PathBuf::from(top.trim()),
+        _ => resolve_git_dir(workspace_path)
+            .await
+            .unwrap_or_else(|_| PathBuf::from(workspace_path)),
+    };
+    base.join(path)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path};
+
+    fn config_for(path: &str, email: &str) -> DitLoopConfigFile {
+        let yaml = format!(
+            "profiles:\n  work:\n    name: Work User\n    email: {}\nworkspaces:\n  - name: repo\n    path: {}\n    profile: work\n",
+            email, path
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unconfigured_workspace_matches() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let check = check_identity(&DitLoopConfigFile::default(), &repo_path(&dir))
+            .await
+            .unwrap();
+        assert!(check.matches);
+        assert_eq!(check.profile, None);
+        assert_eq!(check.actual.email.as_deref(), Some("test@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_matching_email_is_case_insensitive() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let config = config_for(&repo_path(&dir), "Test@Example.com");
+        let check = check_identity(&config, &repo_path(&dir)).await.unwrap();
+        assert!(check.matches);
+        assert_eq!(check.profile.as_deref(), Some("work"));
+    }
+
+    #[tokio::test]
+    async fn test_guard_refuses_mismatch() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let config = config_for(&repo_path(&dir), "work@corp.com");
+        let err = guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::Enforce)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            IdentityError::Mismatch(IdentityMismatch {
+                profile: "work".to_string(),
+                expected_name: "Work User".to_string(),
+                expected_email: "work@corp.com".to_string(),
+                expected_domain: None,
+                actual_name: Some("Test User".to_string()),
+                actual_email: Some("test@example.com".to_string()),
+            })
+        );
+
+        guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::Override)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_guard_auto_fix_writes_local_identity() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let config = config_for(&repo_path(&dir), "work@corp.com");
+        guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::AutoFix)
+            .await
+            .unwrap();
+
+        let email = git(dir.path(), &["config", "--local", "user.email"]);
+        assert_eq!(email.trim(), "work@corp.com");
+        guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::Enforce)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_guard_enforces_email_domain() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let mut config = config_for(&repo_path(&dir), "test@example.com");
+        config.workspaces[0].settings.email_domain = Some("corp.com".to_string());
+        let err = guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::AutoFix)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IdentityError::Mismatch(IdentityMismatch { expected_domain: Some(ref d), .. }) if d == "corp.com"
+        ));
+
+        config.profiles.get_mut("work").unwrap().email = "work@corp.com".to_string();
+        guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::AutoFix)
+            .await
+            .unwrap();
+        let check = check_identity(&config, &repo_path(&dir)).await.unwrap();
+        assert!(check.matches && check.domain_matches);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_profile_is_an_error() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let mut config = config_for(&repo_path(&dir), "work@corp.com");
+        config.workspaces[0].profile = "missing".to_string();
+        let err = guard_identity_with(&config, &repo_path(&dir), IdentityPolicy::Enforce)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, IdentityError::Failed(message) if message.contains("missing")));
+    }
+
+    #[tokio::test]
+    async fn test_nested_repository_uses_parent_workspace() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        git(&nested, &["init", "-q"]);
+        git(&nested, &["config", "user.email", "work@corp.com"]);
+
+        let config = config_for(&repo_path(&dir), "work@corp.com");
+        let check = check_identity(&config, &nested.to_string_lossy())
+            .await
+            .unwrap();
+        assert_eq!(check.profile.as_deref(), Some("work"));
+        assert!(check.matches);
+    }
+
+    #[tokio::test]
+    async fn test_identity_source_reports_local_scope() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let source = git_identity_source(repo_path(&dir)).await.unwrap();
+        let email = source.email.unwrap();
+        assert_eq!(email.value, "test@example.com");
+        assert_eq!(email.scope, ConfigScope::Local);
+        assert_eq!(email.origin, ".git/config");
+    }
+
+    #[tokio::test]
+    async fn test_identity_source_reports_included_file() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let included = dir.path().join(".git").join("profile.gitconfig");
+        std::fs::write(&included, "[user]\n\temail = work@corp.com\n").unwrap();
+        git(dir.path(), &["config", "--local", "--unset", "user.email"]);
+        git(
+            dir.path(),
+            &["config", "--local", "include.path", "profile.gitconfig"],
+        );
+
+        let source = git_identity_source(repo_path(&dir)).await.unwrap();
+        let email = source.email.unwrap();
+        assert_eq!(email.value, "work@corp.com");
+        assert_eq!(email.scope, ConfigScope::Include);
+        assert_eq!(source.name.unwrap().scope, ConfigScope::Local);
+    }
+
+    #[test]
+    fn test_classify_file_scope() {
+        let primary = [PathBuf::from("/home/u/.gitconfig")];
+        assert_eq!(
+            classify_file_scope("global", Path::new("/home/u/.gitconfig"), &primary),
+            ConfigScope::Global
+        );
+        assert_eq!(
+            classify_file_scope(
+                "global",
+                Path::new("/home/u/.ditloop/work.gitconfig"),
+                &primary
+            ),
+            ConfigScope::Include
+        );
+        assert_eq!(
+            classify_file_scope("command", Path::new(""), &[]),
+            ConfigScope::Command
+        );
+    }
+}
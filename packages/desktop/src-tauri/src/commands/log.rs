@@ -0,0 +1,588 @@
+use serde::{Deserialize, Serialize};
+
+use super::git::run_git;
+
+/// Fields requested per commit, separated by NUL in the log output.
+const LOG_FORMAT: &str =
+    "--format=%H%x00%h%x00%P%x00%an%x00%ae%x00%ai%x00%D%x00%G?%x00%GS%x00%s%x00%b";
+const LOG_FIELDS: usize = 11;
+
+/// A git commit entry.
+#[derive(Debug, Serialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    /// Subject line.
+    pub message: String,
+    /// Message body after the subject, without trailing newlines.
+    pub body: String,
+    pub author: String,
+    pub author_email: String,
+    pub date: String,
+    pub parents: Vec<String>,
+    pub refs: Vec<RefDecoration>,
+    pub signature: SignatureStatus,
+    /// Signer reported by the signature, if signed.
+    pub signer: Option<String>,
+    /// Lane layout, absent when the filter hides commits the graph would
+    /// need to connect rows.
+    pub graph: Option<GraphRow>,
+}
+
+/// Verification result of a commit signature (`%G?`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Unsigned,
+    Good,
+    /// Good signature from a key of unknown validity.
+    GoodUntrusted,
+    Bad,
+    ExpiredSignature,
+    ExpiredKey,
+    RevokedKey,
+    /// The key or allowed signers needed to verify is missing.
+    CannotCheck,
+}
+
+impl SignatureStatus {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "G" => SignatureStatus::Good,
+            "U" => SignatureStatus::GoodUntrusted,
+            "B" => SignatureStatus::Bad,
+            "X" => SignatureStatus::ExpiredSignature,
+            "Y" => SignatureStatus::ExpiredKey,
+            "R" => SignatureStatus::RevokedKey,
+            "E" => SignatureStatus::CannotCheck,
+            _ => SignatureStatus::Unsigned,
+        }
+    }
+}
+
+/// A ref pointing at a commit.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefDecoration {
+    /// Short name, e.g. `main`, `origin/main` or `v1.0`.
+    pub name: String,
+    pub kind: RefKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefKind {
+    Head,
+    Branch,
+    Remote,
+    Tag,
+    Other,
+}
+
+/// Lane layout of one commit in the history graph.
+///
+/// Columns are stable between rows: a line that passes through keeps its
+/// column, so rows can be drawn independently.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphRow {
+    /// Column of the commit's node.
+    pub column: usize,
+    /// Columns whose lines from the row above end at this node.
+    pub incoming: Vec<usize>,
+    /// Columns whose lines pass straight through this row.
+    pub passing: Vec<usize>,
+    /// Columns of the lines leaving this node, one per parent.
+    pub outgoing: Vec<usize>,
+    /// Number of columns this row spans.
+    pub width: usize,
+}
+
+/// Filters for `git_log`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Author name or email pattern.
+    pub author: Option<String>,
+    /// Pattern matched against the commit message.
+    pub grep: Option<String>,
+    /// Only commits after this date, in any format git accepts.
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Branch, ref or range to walk instead of HEAD.
+    pub rev: Option<String>,
+    /// Walk every ref instead of `rev`.
+    pub all: bool,
+    /// Only commits touching these paths.
+    pub paths: Vec<String>,
+}
+
+impl LogFilter {
+    /// Whether the filter drops commits without rewriting parents onto the
+    /// ones kept. Path filters simplify history, so their graph still connects.
+    fn breaks_graph(&self) -> bool {
+        self.author.is_some() || self.grep.is_some() || self.since.is_some() || self.until.is_some()
+    }
+}
+
+/// Position in a paginated log, returned with each page.
+///
+/// The walk starts from the commits the refs pointed at when the first page
+/// was read, so new commits don't shift later pages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogCursor {
+    pub tips: Vec<String>,
+    pub offset: usize,
+    /// Graph lanes still open after the last returned commit.
+    pub lanes: Vec<Option<String>>,
+}
+
+/// One page of `git_log`.
+#[derive(Debug, Serialize)]
+pub struct GitLogPage {
+    pub commits: Vec<GitCommit>,
+    /// Cursor for the next page, or `None` at the end of history.
+    pub next_cursor: Option<LogCursor>,
+}
+
+/// Get a page of git log entries with graph layout.
+///
+/// Pass the previous page's `next_cursor` to continue; the filter must stay
+/// the same between pages. Author, message and date filters leave commits
+/// without graph rows.
+#[tauri::command]
+pub async fn git_log(
+    workspace_path: String,
+    count: u32,
+    cursor: Option<LogCursor>,
+    filter: Option<LogFilter>,
+) -> Result<GitLogPage, String> {
+    if count == 0 {
+        return Err("Count must be at least 1".to_string());
+    }
+    let filter = filter.unwrap_or_default();
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => LogCursor {
+            tips: resolve_tips(&workspace_path, &filter).await?,
+            offset: 0,
+            lanes: Vec::new(),
+        },
+    };
+    if cursor.tips.is_empty() {
+        return Ok(GitLogPage {
+            commits: Vec::new(),
+            next_cursor: None,
+        });
+    }
+
+    let count = count as usize;
+    let skip = format!("--skip={}", cursor.offset);
+    // One extra commit tells us whether another page exists.
+    let limit = format!("--max-count={}", count + 1);
+    // `log.showSignature` would mix gpg output into the records.
+    let mut args: Vec<String> = [
+        "log",
+        "-z",
+        "--no-show-signature",
+        "--topo-order",
+        "--decorate=full",
+        LOG_FORMAT,
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .chain([skip, limit])
+    .collect();
+    let options = [
+        ("--author", &filter.author),
+        ("--grep", &filter.grep),
+        ("--since", &filter.since),
+        ("--until", &filter.until),
+    ];
+    for (flag, value) in options {
+        if let Some(value) = value {
+            args.push(format!("{}={}", flag, value));
+        }
+    }
+    if !filter.paths.is_empty() {
+        // Rewrites %P onto the commits that touch the paths.
+        args.push("--parents".to_string());
+    }
+    args.extend(cursor.tips.iter().cloned());
+    args.push("--".to_string());
+    args.extend(filter.paths.iter().cloned());
+
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_git(&workspace_path, &arg_refs).await?;
+    let mut commits = parse_log(&output);
+
+    let has_more = commits.len() > count;
+    commits.truncate(count);
+
+    let mut lanes = cursor.lanes;
+    if !filter.breaks_graph() {
+        for commit in &mut commits {
+            commit.graph = Some(assign_lanes(&mut lanes, &commit.hash, &commit.parents));
+        }
+    }
+
+    let next_cursor = has_more.then(|| LogCursor {
+        tips: cursor.tips,
+        offset: cursor.offset + commits.len(),
+        lanes,
+    });
+    Ok(GitLogPage {
+        commits,
+        next_cursor,
+    })
+}
+
+/// Resolve the starting points of the walk to commit hashes.
+///
+/// A repository without commits has no tips.
+async fn resolve_tips(workspace_path: &str, filter: &LogFilter) -> Result<Vec<String>, String> {
+    let output = if filter.all {
+        run_git(workspace_path, &["rev-parse", "--all"]).await?
+    } else {
+        let rev = filter.rev.as_deref().unwrap_or("HEAD");
+        if rev.starts_with('-') {
+            return Err(format!("Invalid revision '{}'", rev));
+        }
+        match run_git(workspace_path, &["rev-parse", rev]).await {
+            Ok(output) => output,
+            Err(_) if filter.rev.is_none() => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        }
+    };
+
+    let mut tips: Vec<String> = output.lines().map(str::to_string).collect();
+    if filter.all {
+        if let Ok(head) = run_git(workspace_path, &["rev-parse", "HEAD"]).await {
+            tips.push(head.trim().to_string());
+        }
+    }
+    tips.sort();
+    tips.dedup();
+    Ok(tips)
+}
+
+/// Parse `git log -z` output in [`LOG_FORMAT`].
+///
+/// Graph rows are left unset for the caller to fill in.
+pub fn parse_log(output: &str) -> Vec<GitCommit> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks(LOG_FIELDS)
+        .filter(|chunk| chunk.len() == LOG_FIELDS)
+        .map(|f| GitCommit {
+            hash: f[0].to_string(),
+            short_hash: f[1].to_string(),
+            parents: f[2].split_whitespace().map(str::to_string).collect(),
+            author: f[3].to_string(),
+            author_email: f[4].to_string(),
+            date: f[5].to_string(),
+            refs: parse_decorations(f[6]),
+            signature: SignatureStatus::from_code(f[7]),
+            signer: Some(f[8].to_string()).filter(|s| !s.is_empty()),
+            message: f[9].to_string(),
+            body: f[10].trim_end_matches('\n').to_string(),
+            graph: None,
+        })
+        .collect()
+}
+
+/// Parse a `%D` decoration list produced with `--decorate=full`.
+fn parse_decorations(decorations: &str) -> Vec<RefDecoration> {
+    let mut refs = Vec::new();
+    for part in decorations.split(", ").filter(|p| !p.is_empty()) {
+        let full = match part.strip_prefix("HEAD -> ") {
+            Some(branch) => {
+                refs.push(RefDecoration {
+                    name: "HEAD".to_string(),
+                    kind: RefKind::Head,
+                });
+                branch
+            }
+            None => part.strip_prefix("tag: ").unwrap_or(part),
+        };
+
+        let (name, kind) = if full == "HEAD" {
+            (full, RefKind::Head)
+        } else if let Some(name) = full.strip_prefix("refs/heads/") {
+            (name, RefKind::Branch)
+        } else if let Some(name) = full.strip_prefix("refs/remotes/") {
+            (name, RefKind::Remote)
+        } else if let Some(name) = full.strip_prefix("refs/tags/") {
+            (name, RefKind::Tag)
+        } else {
+            (full, RefKind::Other)
+        };
+        refs.push(RefDecoration {
+            name: name.to_string(),
+            kind,
+        });
+    }
+    refs
+}
+
+/// Place a commit in the graph and update the open lanes for its parents.
+///
+/// `lanes` holds, per column, the commit the line in that column leads to.
+pub fn assign_lanes(lanes: &mut Vec<Option<String>>, hash: &str, parents: &[String]) -> GraphRow {
+    let incoming: Vec<usize> = lanes
+        .iter()
+        .enumerate()
+        .filter(|(_, lane)| lane.as_deref() == Some(hash))
+        .map(|(i, _)| i)
+        .collect();
+    let column = match incoming.first() {
+        Some(&column) => column,
+        None => free_lane(lanes),
+    };
+    for &i in &incoming {
+        lanes[i] = None;
+    }
+
+    let passing: Vec<usize> = lanes
+        .iter()
+        .enumerate()
+        .filter(|(_, lane)| lane.is_some())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut outgoing = Vec::new();
+    for (n, parent) in parents.iter().enumerate() {
+        // Join a line already heading to this parent, otherwise the first
+        // parent continues straight down and the rest branch out.
+        let target = match lanes.iter().position(|l| l.as_deref() == Some(parent)) {
+            Some(i) => i,
+            None if n == 0 => column,
+            None => free_lane(lanes),
+        };
+        lanes[target] = Some(parent.clone());
+        outgoing.push(target);
+    }
+
+    let width = lanes.len().max(column + 1);
+    while lanes.last().is_some_and(|lane| lane.is_none()) {
+        lanes.pop();
+    }
+
+    GraphRow {
+        column,
+        incoming,
+        passing,
+        outgoing,
+        width,
+    }
+}
+
+/// Index of the first unused column, adding one if all are taken.
+fn free_lane(lanes: &mut Vec<Option<String>>) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(i) => i,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path, write};
+
+    fn commit(dir: &tempfile::TempDir, message: &str) {
+        git(
+            dir.path(),
+            &["commit", "-q", "--allow-empty", "-m", message],
+        );
+    }
+
+    #[test]
+    fn test_parse_decorations() {
+        let refs = parse_decorations(
+            "HEAD -> refs/heads/main, tag: refs/tags/v1.0, refs/remotes/origin/main, refs/stash",
+        );
+        let names: Vec<_> = refs.iter().map(|r| (r.name.as_str(), r.kind)).collect();
+        assert_eq!(
+            names,
+            [
+                ("HEAD", RefKind::Head),
+                ("main", RefKind::Branch),
+                ("v1.0", RefKind::Tag),
+                ("origin/main", RefKind::Remote),
+                ("refs/stash", RefKind::Other),
+            ]
+        );
+        assert!(parse_decorations("").is_empty());
+    }
+
+    #[test]
+    fn test_assign_lanes_for_merge() {
+        let p = |s: &str| s.to_string();
+        let mut lanes = Vec::new();
+
+        // m merges b (first parent) and c; both fork from a.
+        let m = assign_lanes(&mut lanes, "m", &[p("b"), p("c")]);
+        assert_eq!((m.column, m.outgoing.clone(), m.width), (0, vec![0, 1], 2));
+
+        let c = assign_lanes(&mut lanes, "c", &[p("a")]);
+        assert_eq!(
+            (c.column, c.passing.clone(), c.outgoing.clone()),
+            (1, vec![0], vec![1])
+        );
+
+        // b's parent is already on lane 1, so b's line merges into it.
+        let b = assign_lanes(&mut lanes, "b", &[p("a")]);
+        assert_eq!((b.column, b.outgoing.clone()), (0, vec![1]));
+
+        let a = assign_lanes(&mut lanes, "a", &[]);
+        assert_eq!(
+            (a.column, a.incoming.clone(), a.outgoing.clone()),
+            (1, vec![1], vec![])
+        );
+        assert!(lanes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_keeps_multiline_and_empty_messages() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        git(
+            dir.path(),
+            &[
+                "commit",
+                "-q",
+                "--allow-empty",
+                "--allow-empty-message",
+                "-m",
+                "",
+            ],
+        );
+        commit(&dir, "subject\n\nfirst line\n\x1esecond line");
+        git(dir.path(), &["tag", "v1"]);
+
+        let page = git_log(repo_path(&dir), 10, None, None).await.unwrap();
+        let commits = &page.commits;
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[0].message, "subject");
+        assert_eq!(commits[0].body, "first line\n\x1esecond line");
+        assert_eq!(commits[0].parents, [commits[1].hash.clone()]);
+        assert!(commits[0].refs.contains(&RefDecoration {
+            name: "v1".to_string(),
+            kind: RefKind::Tag
+        }));
+        assert_eq!(commits[1].message, "");
+        assert_eq!(commits[2].message, "initial");
+        assert!(commits[2].parents.is_empty());
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_log_pages_are_stable_when_new_commits_arrive() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        for n in 1..=4 {
+            commit(&dir, &format!("c{}", n));
+        }
+
+        let first = git_log(repo_path(&dir), 2, None, None).await.unwrap();
+        let subjects: Vec<_> = first.commits.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(subjects, ["c4", "c3"]);
+
+        commit(&dir, "c5");
+        let second = git_log(repo_path(&dir), 2, first.next_cursor, None)
+            .await
+            .unwrap();
+        let subjects: Vec<_> = second.commits.iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(subjects, ["c2", "c1"]);
+
+        let last = git_log(repo_path(&dir), 2, second.next_cursor, None)
+            .await
+            .unwrap();
+        assert_eq!(last.commits.len(), 1);
+        assert_eq!(last.commits[0].message, "initial");
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_log_filters() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        write(&dir, "src/lib.rs", "fn main() {}\n");
+        git(dir.path(), &["add", "-A"]);
+        git(
+            dir.path(),
+            &[
+                "commit",
+                "-q",
+                "-m",
+                "Add lib",
+                "--author",
+                "Other <other@example.com>",
+            ],
+        );
+        commit(&dir, "Fix typo");
+        git(dir.path(), &["branch", "side", "HEAD~1"]);
+
+        let log = |filter: LogFilter| git_log(repo_path(&dir), 10, None, Some(filter));
+        let subjects = |page: GitLogPage| -> Vec<String> {
+            page.commits.into_iter().map(|c| c.message).collect()
+        };
+
+        let by_author = log(LogFilter {
+            author: Some("other@".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(subjects(by_author.await.unwrap()), ["Add lib"]);
+
+        let by_path = log(LogFilter {
+            paths: vec!["src".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        // The parent is rewritten away from the commits not shown.
+        assert!(by_path.commits[0].parents.is_empty());
+        assert!(by_path.commits[0]
+            .graph
+            .as_ref()
+            .unwrap()
+            .outgoing
+            .is_empty());
+        assert_eq!(subjects(by_path), ["Add lib"]);
+
+        let by_grep = log(LogFilter {
+            grep: Some("typo".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(by_grep.commits[0].graph.is_none());
+        assert_eq!(subjects(by_grep), ["Fix typo"]);
+
+        let by_rev = log(LogFilter {
+            rev: Some("side".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(subjects(by_rev.await.unwrap()), ["Add lib", "initial"]);
+
+        let bad_rev = log(LogFilter {
+            rev: Some("--output=x".to_string()),
+            ..Default::default()
+        });
+        assert!(bad_rev.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_log_empty_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "-q"]);
+        let page = git_log(repo_path(&dir), 10, None, None).await.unwrap();
+        assert!(page.commits.is_empty());
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_log_rejects_zero_count() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        assert!(git_log(repo_path(&dir), 0, None, None).await.is_err());
+    }
+}
@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::State;
+use tokio::process::Command;
+
+use super::config::DitLoopConfigFile;
+use super::config_state::ConfigState;
+use super::git::{
+    parse_porcelain_v2, profile_signing, run_git, signing_args, CommitError, ConflictEntry,
+};
+use super::identity::{guard_identity_with, IdentityPolicy};
+use super::workspace_settings::protected_branch;
+
+/// Kind of multi-step operation in progress in a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    None,
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+/// Current merge/rebase/cherry-pick/revert state of a repository.
+#[derive(Debug, Serialize)]
+pub struct OperationState {
+    pub kind: OperationKind,
+    /// Commit being merged, picked or reverted, or the rebase target.
+    pub incoming: Option<String>,
+    /// Current and total step of a rebase.
+    pub step: Option<u32>,
+    pub total_steps: Option<u32>,
+    pub conflicted: Vec<ConflictEntry>,
+}
+
+/// A conflicted file with the blob contents of each merge stage.
+///
+/// Contents are `None` when the stage is absent (e.g. deleted on one side)
+/// or the blob is binary.
+#[derive(Debug, Serialize)]
+pub struct ConflictFile {
+    pub path: String,
+    pub code: String,
+    pub status: String,
+    pub base: Option<String>,
+    pub ours: Option<String>,
+    pub theirs: Option<String>,
+    pub is_binary: bool,
+}
+
+/// Side of a conflict to keep.
+///
+/// During a rebase git swaps the meaning: "ours" is the branch being
+/// rebased onto and "theirs" is the commit being replayed.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+/// Detect whether a merge, rebase, cherry-pick or revert is in progress.
+#[tauri::command]
+pub async fn git_operation_state(workspace_path: String) -> Result<OperationState, String> {
+    let git_dir = resolve_git_dir(&workspace_path).await?;
+    let read = |name: &str| {
+        std::fs::read_to_string(git_dir.join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    let mut state = OperationState {
+        kind: OperationKind::None,
+        incoming: None,
+        step: None,
+        total_steps: None,
+        conflicted: Vec::new(),
+    };
+
+    if let Some(dir) = ["rebase-merge", "rebase-apply"]
+        .iter()
+        .find(|d| git_dir.join(d).is_dir())
+    {
+        // `git am` also uses rebase-apply, marked by an "applying" file.
+        if !git_dir.join(dir).join("applying").exists() {
+            state.kind = OperationKind::Rebase;
+            state.incoming = read(&format!("{}/onto", dir));
+            let (step, total) = if *dir == "rebase-merge" {
+                ("msgnum", "end")
+            } else {
+                ("next", "last")
+            };
+            state.step = read(&format!("{}/{}", dir, step)).and_then(|s| s.parse().ok());
+            state.total_steps = read(&format!("{}/{}", dir, total)).and_then(|s| s.parse().ok());
+        }
+    } else if let Some(head) = read("MERGE_HEAD") {
+        state.kind = OperationKind::Merge;
+        state.incoming = head.lines().next().map(|s| s.to_string());
+    } else if let Some(head) = read("CHERRY_PICK_HEAD") {
+        state.kind = OperationKind::CherryPick;
+        state.incoming = Some(head);
+    } else if let Some(head) = read("REVERT_HEAD") {
+        state.kind = OperationKind::Revert;
+        state.incoming = Some(head);
+    }
+
+    state.conflicted = conflicted_entries(&workspace_path).await?;
+    Ok(state)
+}
+
+/// List conflicted files with their base, ours and theirs contents.
+#[tauri::command]
+pub async fn git_conflict_list(workspace_path: String) -> Result<Vec<ConflictFile>, String> {
+    let mut files = Vec::new();
+    for entry in conflicted_entries(&workspace_path).await? {
+        let base = read_stage(&workspace_path, 1, &entry.path).await?;
+        let ours = read_stage(&workspace_path, 2, &entry.path).await?;
+        let theirs = read_stage(&workspace_path, 3, &entry.path).await?;
+        let is_binary = [&base, &ours, &theirs]
+            .iter()
+            .any(|blob| blob.as_ref().is_some_and(|b| b.contains(&0)));
+
+        let text = |blob: Option<Vec<u8>>| {
+            blob.filter(|_| !is_binary)
+                .map(|b| String::from_utf8_lossy(&b).to_string())
+        };
+        files.push(ConflictFile {
+            path: entry.path,
+            code: entry.code,
+            status: entry.status,
+            base: text(base),
+            ours: text(ours),
+            theirs: text(theirs),
+            is_binary,
+        });
+    }
+    Ok(files)
+}
+
+/// Mark conflicted paths as resolved by staging their working tree state.
+///
+/// Files still containing conflict markers are refused unless `force` is set.
+#[tauri::command]
+pub async fn git_mark_resolved(
+    workspace_path: String,
+    paths: Vec<String>,
+    force: Option<bool>,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    if !force.unwrap_or(false) {
+        let root = PathBuf::from(&workspace_path);
+        let unresolved: Vec<&str> = paths
+            .iter()
+            .filter(|p| {
+                std::fs::read_to_string(root.join(p))
+                    .map(|content| has_conflict_markers(&content))
+                    .unwrap_or(false)
+            })
+            .map(String::as_str)
+            .collect();
+        if !unresolved.is_empty() {
+            return Err(format!(
+                "Conflict markers still present in: {}",
+                unresolved.join(", ")
+            ));
+        }
+    }
+
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    run_git(&workspace_path, &args).await?;
+    Ok(())
+}
+
+/// Resolve a conflicted file by taking one side entirely.
+#[tauri::command]
+pub async fn git_resolve_take(
+    workspace_path: String,
+    path: String,
+    side: ConflictSide,
+) -> Result<(), String> {
+    let stage = match side {
+        ConflictSide::Ours => 2,
+        ConflictSide::Theirs => 3,
+    };
+
+    // A missing stage means that side deleted the file.
+    if read_stage(&workspace_path, stage, &path).await?.is_none() {
+        run_git(&workspace_path, &["rm", "-q", "--", &path]).await?;
+        return Ok(());
+    }
+
+    let flag = match side {
+        ConflictSide::Ours => "--ours",
+        ConflictSide::Theirs => "--theirs",
+    };
+    run_git(&workspace_path, &["checkout", flag, "--", &path]).await?;
+    run_git(&workspace_path, &["add", "--", &path]).await?;
+    Ok(())
+}
+
+/// Continue the in-progress operation once all conflicts are resolved.
+///
+/// The commits it makes go through the same checks as `git_commit`: the
+/// protected branch and identity guards, and the profile's signing.
+#[tauri::command]
+pub async fn git_operation_continue(
+    config_state: State<'_, ConfigState>,
+    workspace_path: String,
+    identity_policy: Option<IdentityPolicy>,
+) -> Result<(), CommitError> {
+    let config = config_state.config()?.unwrap_or_default();
+    continue_operation(
+        &config,
+        &workspace_path,
+        identity_policy.unwrap_or_default(),
+    )
+    .await
+}
+
+async fn continue_operation(
+    config: &DitLoopConfigFile,
+    workspace_path: &str,
+    identity_policy: IdentityPolicy,
+) -> Result<(), CommitError> {
+    let state = git_operation_state(workspace_path.to_string()).await?;
+    if !state.conflicted.is_empty() {
+        return Err(format!("{} file(s) still have conflicts", state.conflicted.len()).into());
+    }
+
+    let command: &[&str] = match state.kind {
+        OperationKind::None => return Err("No operation in progress".to_string().into()),
+        OperationKind::Merge => &["commit", "--no-edit"],
+        OperationKind::Rebase => &["rebase", "--continue"],
+        OperationKind::CherryPick => &["cherry-pick", "--continue"],
+        OperationKind::Revert => &["revert", "--continue"],
+    };
+    if let Some(branch) = protected_branch(config, workspace_path).await {
+        return Err(CommitError::ProtectedBranch { branch });
+    }
+    guard_identity_with(config, workspace_path, identity_policy).await?;
+
+    let mut args = profile_signing(config, workspace_path)
+        .map(signing_args)
+        .unwrap_or_default();
+    args.extend(command.iter().map(|arg| arg.to_string()));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    Ok(run_non_interactive(workspace_path, &args).await?)
+}
+
+/// Abort the in-progress operation and restore the pre-operation state.
+#[tauri::command]
+pub async fn git_operation_abort(workspace_path: String) -> Result<(), String> {
+    let state = git_operation_state(workspace_path.clone()).await?;
+    let args: &[&str] = match state.kind {
+        OperationKind::None => return Err("No operation in progress".to_string()),
+        OperationKind::Merge => &["merge", "--abort"],
+        OperationKind::Rebase => &["rebase", "--abort"],
+        OperationKind::CherryPick => &["cherry-pick", "--abort"],
+        OperationKind::Revert => &["revert", "--abort"],
+    };
+    run_non_interactive(&workspace_path, args).await
+}
+
+/// Absolute path of the repository's git directory.
+pub(crate) async fn resolve_git_dir(workspace_path: &str) -> Result<PathBuf, String> {
+    let output = run_git(workspace_path, &["rev-parse", "--absolute-git-dir"]).await?;
+    Ok(PathBuf::from(output.trim()))
+}
+
+/// Unmerged entries from git status.
+async fn conflicted_entries(workspace_path: &str) -> Result<Vec<ConflictEntry>, String> {
+    let output = run_git(workspace_path, &["status", "--porcelain=v2", "-z"]).await?;
+    Ok(parse_porcelain_v2(&output).conflicted)
+}
+
+/// Read the blob at a merge stage (1 base, 2 ours, 3 theirs), if present.
+async fn read_stage(
+    workspace_path: &str,
+    stage: u8,
+    path: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let output = Command::new("git")
+        .args(["cat-file", "blob", &format!(":{}:{}", stage, path)])
+        .current_dir(workspace_path)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(output.status.success().then_some(output.stdout))
+}
+
+/// Run git with editors disabled so continue steps never block on input.
+async fn run_non_interactive(workspace_path: &str, args: &[&str]) -> Result<(), String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workspace_path)
+        .env("GIT_EDITOR", "true")
+        .env("GIT_SEQUENCE_EDITOR", "true")
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        } else {
+            stderr
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether text still contains a complete set of conflict markers.
+fn has_conflict_markers(content: &str) -> bool {
+    let mut seen_start = false;
+    let mut seen_separator = false;
+    for line in content.lines() {
+        if line.starts_with("<<<<<<< ") {
+            seen_start = true;
+        } else if seen_start && line == "=======" {
+            seen_separator = true;
+        } else if seen_separator && line.starts_with(">>>>>>> ") {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, read, repo_path, write};
+
+    /// Create a repository where merging `feature` into `main` conflicts on `f.txt`.
+    fn conflicting_repo() -> tempfile::TempDir {
+        let dir = init_repo(&[("f.txt", "base\n")]);
+        git(dir.path(), &["checkout", "-q", "-b", "feature"]);
+        write(&dir, "f.txt", "theirs\n");
+        git(dir.path(), &["commit", "-q", "-am", "feature change"]);
+        git(dir.path(), &["checkout", "-q", "main"]);
+        write(&dir, "f.txt", "ours\n");
+        git(dir.path(), &["commit", "-q", "-am", "main change"]);
+        dir
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        assert!(has_conflict_markers(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\n"
+        ));
+        assert!(!has_conflict_markers("a\n=======\nheading underline\n"));
+        assert!(!has_conflict_markers("resolved\n"));
+    }
+
+    #[tokio::test]
+    async fn test_operation_state_none() {
+        let dir = init_repo(&[("f.txt", "a\n")]);
+        let state = git_operation_state(repo_path(&dir)).await.unwrap();
+        assert_eq!(state.kind, OperationKind::None);
+        assert!(state.conflicted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_merge_conflict_take_theirs_and_continue() {
+        let dir = conflicting_repo();
+        let path = repo_path(&dir);
+        let _ = std::process::Command::new("git")
+            .args(["merge", "feature"])
+            .current_dir(dir.path())
+            .output();
+
+        let state = git_operation_state(path.clone()).await.unwrap();
+        assert_eq!(state.kind, OperationKind::Merge);
+        assert!(state.incoming.is_some());
+        assert_eq!(state.conflicted.len(), 1);
+
+        let conflicts = git_conflict_list(path.clone()).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "f.txt");
+        assert_eq!(conflicts[0].code, "UU");
+        assert_eq!(conflicts[0].base.as_deref(), Some("base\n"));
+        assert_eq!(conflicts[0].ours.as_deref(), Some("ours\n"));
+        assert_eq!(conflicts[0].theirs.as_deref(), Some("theirs\n"));
+
+        assert!(continue_operation(
+            &DitLoopConfigFile::default(),
+            &path,
+            IdentityPolicy::Enforce
+        )
+        .await
+        .is_err());
+        assert!(git_mark_resolved(path.clone(), vec!["f.txt".into()], None)
+            .await
+            .is_err());
+
+        git_resolve_take(path.clone(), "f.txt".into(), ConflictSide::Theirs)
+            .await
+            .unwrap();
+        assert_eq!(read(&dir, "f.txt"), "theirs\n");
+
+        continue_operation(
+            &DitLoopConfigFile::default(),
+            &path,
+            IdentityPolicy::Enforce,
+        )
+        .await
+        .unwrap();
+        let state = git_operation_state(path).await.unwrap();
+        assert_eq!(state.kind, OperationKind::None);
+        let parents = git(dir.path(), &["rev-list", "--parents", "-n", "1", "HEAD"]);
+        assert_eq!(parents.split_whitespace().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_merge_conflict_manual_resolution_and_abort() {
+        let dir = conflicting_repo();
+        let path = repo_path(&dir);
+        let _ = std::process::Command::new("git")
+            .args(["merge", "feature"])
+            .current_dir(dir.path())
+            .output();
+
+        write(&dir, "f.txt", "combined\n");
+        git_mark_resolved(path.clone(), vec!["f.txt".into()], None)
+            .await
+            .unwrap();
+        let state = git_operation_state(path.clone()).await.unwrap();
+        assert_eq!(state.kind, OperationKind::Merge);
+        assert!(state.conflicted.is_empty());
+
+        git_operation_abort(path.clone()).await.unwrap();
+        let state = git_operation_state(path).await.unwrap();
+        assert_eq!(state.kind, OperationKind::None);
+        assert_eq!(read(&dir, "f.txt"), "ours\n");
+    }
+
+    #[tokio::test]
+    async fn test_rebase_conflict_state_and_continue() {
+        let dir = conflicting_repo();
+        let path = repo_path(&dir);
+        git(dir.path(), &["checkout", "-q", "feature"]);
+        let _ = std::process::Command::new("git")
+            .args(["rebase", "main"])
+            .current_dir(dir.path())
+            .output();
+
+        let state = git_operation_state(path.clone()).await.unwrap();
+        assert_eq!(state.kind, OperationKind::Rebase);
+        assert_eq!(state.step, Some(1));
+        assert_eq!(state.total_steps, Some(1));
+
+        // In a rebase "ours" is the upstream being rebased onto.
+        git_resolve_take(path.clone(), "f.txt".into(), ConflictSide::Ours)
+            .await
+            .unwrap();
+        assert_eq!(read(&dir, "f.txt"), "ours\n");
+        continue_operation(
+            &DitLoopConfigFile::default(),
+            &path,
+            IdentityPolicy::Enforce,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            git_operation_state(path).await.unwrap().kind,
+            OperationKind::None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cherry_pick_conflict_take_deleted_side() {
+        let dir = init_repo(&[("f.txt", "base\n")]);
+        let path = repo_path(&dir);
+        git(dir.path(), &["checkout", "-q", "-b", "feature"]);
+        git(dir.path(), &["rm", "-q", "f.txt"]);
+        git(dir.path(), &["commit", "-q", "-m", "remove"]);
+        git(dir.path(), &["checkout", "-q", "main"]);
+        write(&dir, "f.txt", "edited\n");
+        git(dir.path(), &["commit", "-q", "-am", "edit"]);
+        let _ = std::process::Command::new("git")
+            .args(["cherry-pick", "feature"])
+            .current_dir(dir.path())
+            .output();
+
+        let state = git_operation_state(path.clone()).await.unwrap();
+        assert_eq!(state.kind, OperationKind::CherryPick);
+        let conflicts = git_conflict_list(path.clone()).await.unwrap();
+        assert_eq!(conflicts[0].theirs, None);
+        assert_eq!(conflicts[0].ours.as_deref(), Some("edited\n"));
+
+        git_resolve_take(path.clone(), "f.txt".into(), ConflictSide::Theirs)
+            .await
+            .unwrap();
+        assert!(!dir.path().join("f.txt").exists());
+        assert!(git_operation_state(path)
+            .await
+            .unwrap()
+            .conflicted
+            .is_empty());
+    }
+}
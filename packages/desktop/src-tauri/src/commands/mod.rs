@@ -1,9 +1,27 @@
 pub mod ai_cli;
+pub mod branch;
 pub mod config;
+pub mod config_edit;
+pub mod config_migrate;
+pub mod config_state;
+pub mod config_validate;
+pub mod diff;
 pub mod filesystem;
 pub mod git;
+pub mod identity;
+pub mod log;
+pub mod merge;
+pub mod remote;
+pub mod repo;
 pub mod server;
+pub mod ssh;
+pub mod watcher;
 pub mod workspace;
+pub mod workspace_settings;
+pub mod worktree;
+
+#[cfg(test)]
+mod test_support;
 
 /// Placeholder command for testing IPC between frontend and Rust backend.
 #[tauri::command]
@@ -0,0 +1,680 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager, State, Window};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::Notify;
+
+use super::config_state::ConfigState;
+use super::git::run_git;
+use super::identity::{
+    guard_identity, guard_identity_with, IdentityError, IdentityMismatch, IdentityPolicy,
+};
+use super::workspace_settings::protected_branch;
+
+/// Event emitted for every progress line of a running remote operation.
+pub const PROGRESS_EVENT: &str = "git:progress";
+
+/// A configured git remote.
+#[derive(Debug, Serialize)]
+pub struct GitRemote {
+    pub name: String,
+    pub fetch_url: String,
+    pub push_url: String,
+}
+
+/// Progress line payload for [`PROGRESS_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteProgress {
+    pub operation_id: String,
+    pub line: String,
+}
+
+/// How `git_pull` integrates upstream changes.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStrategy {
+    FfOnly,
+    Merge,
+    Rebase,
+}
+
+/// Structured failure of a fetch, pull or push.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteError {
+    AuthFailed { message: String },
+    NonFastForward { message: String },
+    StaleLease { message: String },
+    HookRejected { message: String },
+    NoUpstream { message: String },
+    IdentityMismatch(IdentityMismatch),
+    ProtectedBranch { branch: String },
+    Cancelled,
+    Other { message: String },
+}
+
+impl From<IdentityError> for RemoteError {
+    fn from(error: IdentityError) -> Self {
+        match error {
+            IdentityError::Mismatch(mismatch) => RemoteError::IdentityMismatch(mismatch),
+            IdentityError::Failed(message) => RemoteError::Other { message },
+        }
+    }
+}
+
+/// Running remote operations that can be cancelled by id.
+#[derive(Default)]
+pub struct RemoteOperations {
+    running: Mutex<HashMap<String, Arc<Notify>>>,
+}
+
+impl RemoteOperations {
+    fn start(&self, operation_id: &str) -> Arc<Notify> {
+        let cancel = Arc::new(Notify::new());
+        if let Ok(mut running) = self.running.lock() {
+            running.insert(operation_id.to_string(), cancel.clone());
+        }
+        cancel
+    }
+
+    fn finish(&self, operation_id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(operation_id);
+        }
+    }
+
+    fn cancel(&self, operation_id: &str) -> bool {
+        match self.running.lock() {
+            Ok(running) => match running.get(operation_id) {
+                Some(cancel) => {
+                    cancel.notify_one();
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+/// List the workspace's remotes with their fetch and push URLs.
+#[tauri::command]
+pub async fn git_remote_list(workspace_path: String) -> Result<Vec<GitRemote>, String> {
+    let output = run_git(&workspace_path, &["remote", "-v"]).await?;
+    let mut remotes: Vec<GitRemote> = Vec::new();
+
+    for line in output.lines() {
+        // Lines look like "origin\t<url> (fetch)"; URLs may contain spaces.
+        let Some((name, rest)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some((url, kind)) = rest.rsplit_once(' ') else {
+            continue;
+        };
+        let index = match remotes.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                remotes.push(GitRemote {
+                    name: name.to_string(),
+                    fetch_url: String::new(),
+                    push_url: String::new(),
+                });
+                remotes.len() - 1
+            }
+        };
+        if kind == "(push)" {
+            remotes[index].push_url = url.to_string();
+        } else {
+            remotes[index].fetch_url = url.to_string();
+        }
+    }
+
+    Ok(remotes)
+}
+
+/// Fetch from one remote, or from all remotes when `remote` is not given.
+#[tauri::command]
+pub async fn git_fetch(
+    window: Window,
+    operations: State<'_, RemoteOperations>,
+    workspace_path: String,
+    operation_id: String,
+    remote: Option<String>,
+    prune: Option<bool>,
+) -> Result<String, RemoteError> {
+    let args = fetch_args(remote.as_deref(), prune.unwrap_or(false));
+    run_tracked(&window, &operations, &workspace_path, &operation_id, &args).await
+}
+
+/// Pull the current branch, by default honoring the user's pull config.
+///
+/// A `branch` without a `remote` is pulled from the current branch's
+/// upstream remote, or `origin` when it has none. A pull can create a
+/// merge commit, so the identity is checked first.
+#[tauri::command]
+pub async fn git_pull(
+    window: Window,
+    workspace_path: String,
+    operation_id: String,
+    remote: Option<String>,
+    branch: Option<String>,
+    strategy: Option<PullStrategy>,
+    identity_policy: Option<IdentityPolicy>,
+) -> Result<String, RemoteError> {
+    guard_identity(
+        &window.state::<ConfigState>(),
+        &workspace_path,
+        identity_policy.unwrap_or_default(),
+    )
+    .await?;
+
+    let operations = window.state::<RemoteOperations>();
+    let remote = match (remote, &branch) {
+        (None, Some(_)) => upstream_remote(&workspace_path).await,
+        (remote, _) => remote,
+    };
+    let args = pull_args(remote.as_deref(), branch.as_deref(), strategy);
+    run_tracked(&window, &operations, &workspace_path, &operation_id, &args).await
+}
+
+/// Push the current branch.
+///
+/// The upstream is set automatically when the branch does not track one yet.
+/// Like `git_commit`, the push is refused when the workspace's identity does
+/// not match its profile unless `identity_policy` says otherwise. A force
+/// push to a protected branch is refused.
+#[tauri::command]
+pub async fn git_push(
+    window: Window,
+    operations: State<'_, RemoteOperations>,
+    workspace_path: String,
+    operation_id: String,
+    remote: Option<String>,
+    force_with_lease: Option<bool>,
+    identity_policy: Option<IdentityPolicy>,
+) -> Result<String, RemoteError> {
+    let config = window
+        .state::<ConfigState>()
+        .config()
+        .map_err(|message| RemoteError::Other { message })?
+        .unwrap_or_default();
+    let force_with_lease = force_with_lease.unwrap_or(false);
+    if force_with_lease {
+        if let Some(branch) = protected_branch(&config, &workspace_path).await {
+            return Err(RemoteError::ProtectedBranch { branch });
+        }
+    }
+    guard_identity_with(
+        &config,
+        &workspace_path,
+        identity_policy.unwrap_or_default(),
+    )
+    .await?;
+
+    let args = push_args(&workspace_path, remote.as_deref(), force_with_lease).await;
+    run_tracked(&window, &operations, &workspace_path, &operation_id, &args).await
+}
+
+/// Cancel a running fetch, pull or push. Returns false if it already finished.
+#[tauri::command]
+pub fn git_remote_cancel(operations: State<'_, RemoteOperations>, operation_id: String) -> bool {
+    operations.cancel(&operation_id)
+}
+
+/// Run a remote git command, emitting progress to the window while it runs.
+async fn run_tracked(
+    window: &Window,
+    operations: &RemoteOperations,
+    workspace_path: &str,
+    operation_id: &str,
+    args: &[String],
+) -> Result<String, RemoteError> {
+    let cancel = operations.start(operation_id);
+    let result = run_remote_git(workspace_path, args, &cancel, |line| {
+        let _ = window.emit(
+            PROGRESS_EVENT,
+            RemoteProgress {
+                operation_id: operation_id.to_string(),
+                line: line.to_string(),
+            },
+        );
+    })
+    .await;
+    operations.finish(operation_id);
+    result
+}
+
+fn fetch_args(remote: Option<&str>, prune: bool) -> Vec<String> {
+    let mut args = vec!["fetch".to_string(), "--progress".to_string()];
+    if prune {
+        args.push("--prune".to_string());
+    }
+    match remote {
+        Some(remote) => args.push(remote.to_string()),
+        None => args.push("--all".to_string()),
+    }
+    args
+}
+
+fn pull_args(
+    remote: Option<&str>,
+    branch: Option<&str>,
+    strategy: Option<PullStrategy>,
+) -> Vec<String> {
+    let mut args = vec![
+        "pull".to_string(),
+        "--progress".to_string(),
+        "--no-edit".to_string(),
+    ];
+    match strategy {
+        Some(PullStrategy::FfOnly) => args.push("--ff-only".to_string()),
+        Some(PullStrategy::Merge) => args.push("--no-rebase".to_string()),
+        Some(PullStrategy::Rebase) => args.push("--rebase".to_string()),
+        None => {}
+    }
+    match (remote, branch) {
+        (Some(remote), Some(branch)) => args.extend([remote.to_string(), branch.to_string()]),
+        (Some(remote), None) => args.push(remote.to_string()),
+        // git only takes a branch after a remote.
+        (None, Some(branch)) => args.extend(["origin".to_string(), branch.to_string()]),
+        (None, None) => {}
+    }
+    args
+}
+
+/// Remote the current branch tracks, if it tracks one.
+async fn upstream_remote(workspace_path: &str) -> Option<String> {
+    let branch = run_git(workspace_path, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .await
+        .ok()?;
+    let key = format!("branch.{}.remote", branch.trim());
+    let remote = run_git(workspace_path, &["config", "--get", &key])
+        .await
+        .ok()?;
+    Some(remote.trim().to_string()).filter(|r| !r.is_empty())
+}
+
+/// Build push arguments, adding `--set-upstream` on a branch's first push.
+async fn push_args(
+    workspace_path: &str,
+    remote: Option<&str>,
+    force_with_lease: bool,
+) -> Vec<String> {
+    let mut args = vec!["push".to_string(), "--progress".to_string()];
+    if force_with_lease {
+        args.push("--force-with-lease".to_string());
+    }
+
+    let has_upstream = run_git(
+        workspace_path,
+        &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+    )
+    .await
+    .is_ok();
+
+    if !has_upstream {
+        args.push("--set-upstream".to_string());
+        args.push(remote.unwrap_or("origin").to_string());
+        args.push("HEAD".to_string());
+    } else if let Some(remote) = remote {
+        args.push(remote.to_string());
+        args.push("HEAD".to_string());
+    }
+    args
+}
+
+/// Run git against a remote, streaming progress lines to `on_progress`.
+///
+/// Credential prompts are disabled so auth problems fail instead of hanging.
+/// Resolves to the command's output once it exits, or `Cancelled` as soon as
+/// `cancel` is notified.
+pub(crate) async fn run_remote_git<F: FnMut(&str)>(
+    workspace_path: &str,
+    args: &[String],
+    cancel: &Notify,
+    mut on_progress: F,
+) -> Result<String, RemoteError> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(workspace_path)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| RemoteError::Other {
+            message: e.to_string(),
+        })?;
+
+    let (Some(mut stdout), Some(mut stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(RemoteError::Other {
+            message: "Failed to capture git output".to_string(),
+        });
+    };
+    let stdout_task = tokio::spawn(async move {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out).await;
+        out
+    });
+
+    // Progress updates are terminated by '\r', finished lines by '\n'.
+    let mut lines: Vec<String> = Vec::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        tokio::select! {
+            _ = cancel.notified() => {
+                let _ = child.kill().await;
+                return Err(RemoteError::Cancelled);
+            }
+            read = stderr.read(&mut buf) => {
+                let n = read.map_err(|e| RemoteError::Other { message: e.to_string() })?;
+                if n == 0 {
+                    break;
+                }
+                for &byte in &buf[..n] {
+                    if byte != b'\r' && byte != b'\n' {
+                        pending.push(byte);
+                        continue;
+                    }
+                    let line = String::from_utf8_lossy(&pending).trim_end().to_string();
+                    pending.clear();
+                    if line.is_empty() {
+                        continue;
+                    }
+                    on_progress(&line);
+                    if byte == b'\n' {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+    }
+    if !pending.is_empty() {
+        let line = String::from_utf8_lossy(&pending).trim_end().to_string();
+        on_progress(&line);
+        lines.push(line);
+    }
+
+    let status = child.wait().await.map_err(|e| RemoteError::Other {
+        message: e.to_string(),
+    })?;
+    let stdout = stdout_task.await.unwrap_or_default();
+    let stderr = lines.join("\n");
+
+    if !status.success() {
+        return Err(classify_remote_error(&stderr));
+    }
+
+    Ok(format!("{}{}", stdout, stderr).trim().to_string())
+}
+
+/// Map git's stderr from a failed remote command to a structured error.
+pub fn classify_remote_error(stderr: &str) -> RemoteError {
+    let message = stderr.trim().to_string();
+    let lower = message.to_lowercase();
+
+    if lower.contains("stale info") {
+        RemoteError::StaleLease { message }
+    } else if lower.contains("hook declined") || lower.contains("[remote rejected]") {
+        RemoteError::HookRejected { message }
+    } else if lower.contains("non-fast-forward")
+        || lower.contains("(fetch first)")
+        || lower.contains("not possible to fast-forward")
+        || lower.contains("divergent branches")
+    {
+        RemoteError::NonFastForward { message }
+    } else if is_auth_error(&lower) {
+        RemoteError::AuthFailed { message }
+    } else if lower.contains("no upstream") || lower.contains("no tracking information") {
+        RemoteError::NoUpstream { message }
+    } else {
+        RemoteError::Other { message }
+    }
+}
+
+/// Git's messages for rejected credentials, over HTTPS or SSH. Matched
+/// narrowly so a local "Permission denied" or a hash containing "403" is
+/// not taken for an auth failure.
+fn is_auth_error(lower: &str) -> bool {
+    lower.contains("authentication failed")
+        || lower.contains("could not read username")
+        || lower.contains("could not read password")
+        || lower.contains("terminal prompts disabled")
+        || lower.contains("the requested url returned error: 401")
+        || lower.contains("the requested url returned error: 403")
+        // ssh lists the methods it tried, e.g. "(publickey)".
+        || lower.contains("permission denied (")
+        || lower
+            .lines()
+            .any(|line| line.starts_with("remote:") && line.contains("access denied"))
+        || (lower.contains("remote: permission to") && lower.contains("denied to"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path, write};
+
+    /// A working repository with an empty bare repository as `origin`.
+    fn repo_with_remote() -> (tempfile::TempDir, tempfile::TempDir) {
+        let dir = init_repo(&[("f.txt", "one\n")]);
+        let remote = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init", "-q", "--bare", "-b", "main"]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", &remote.path().to_string_lossy()],
+        );
+        (dir, remote)
+    }
+
+    /// Clone `remote` into a second working copy with a local identity.
+    fn clone_remote(remote: &tempfile::TempDir) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git(
+            dir.path(),
+            &["clone", "-q", &remote.path().to_string_lossy(), "."],
+        );
+        git(dir.path(), &["config", "user.name", "Other User"]);
+        git(dir.path(), &["config", "user.email", "other@example.com"]);
+        dir
+    }
+
+    async fn run(dir: &tempfile::TempDir, args: Vec<String>) -> Result<String, RemoteError> {
+        run_remote_git(&repo_path(dir), &args, &Notify::new(), |_| {}).await
+    }
+
+    #[test]
+    fn test_classify_remote_error() {
+        let auth =
+            "fatal: could not read Username for 'https://github.com': terminal prompts disabled";
+        assert!(matches!(
+            classify_remote_error(auth),
+            RemoteError::AuthFailed { .. }
+        ));
+        let ssh = "git@github.com: Permission denied (publickey).\nfatal: Could not read from remote repository.";
+        assert!(matches!(
+            classify_remote_error(ssh),
+            RemoteError::AuthFailed { .. }
+        ));
+        let rejected =
+            " ! [rejected]        main -> main (fetch first)\nerror: failed to push some refs";
+        assert!(matches!(
+            classify_remote_error(rejected),
+            RemoteError::NonFastForward { .. }
+        ));
+        let hook = " ! [remote rejected] main -> main (pre-receive hook declined)";
+        assert!(matches!(
+            classify_remote_error(hook),
+            RemoteError::HookRejected { .. }
+        ));
+        assert!(matches!(
+            classify_remote_error("fatal: something else"),
+            RemoteError::Other { .. }
+        ));
+
+        let http = "remote: Permission to org/repo.git denied to someone.\nfatal: unable to access 'https://github.com/org/repo.git/': The requested URL returned error: 403";
+        assert!(matches!(
+            classify_remote_error(http),
+            RemoteError::AuthFailed { .. }
+        ));
+        let local = "error: cannot open .git/FETCH_HEAD: Permission denied";
+        assert!(matches!(
+            classify_remote_error(local),
+            RemoteError::Other { .. }
+        ));
+        let hash = "fatal: bad object 4031a2b";
+        assert!(matches!(
+            classify_remote_error(hash),
+            RemoteError::Other { .. }
+        ));
+    }
+
+    #[test]
+    fn test_fetch_and_pull_args() {
+        assert_eq!(
+            fetch_args(None, true),
+            vec!["fetch", "--progress", "--prune", "--all"]
+        );
+        assert_eq!(
+            fetch_args(Some("upstream"), false),
+            vec!["fetch", "--progress", "upstream"]
+        );
+        assert_eq!(
+            pull_args(Some("origin"), Some("main"), Some(PullStrategy::Rebase)),
+            vec![
+                "pull",
+                "--progress",
+                "--no-edit",
+                "--rebase",
+                "origin",
+                "main"
+            ]
+        );
+        assert_eq!(
+            pull_args(None, Some("main"), None),
+            vec!["pull", "--progress", "--no-edit", "origin", "main"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_first_push_sets_upstream() {
+        let (dir, _remote) = repo_with_remote();
+        let path = repo_path(&dir);
+
+        assert_eq!(upstream_remote(&path).await, None);
+        let args = push_args(&path, None, false).await;
+        assert!(args.contains(&"--set-upstream".to_string()));
+        let mut progress = Vec::new();
+        run_remote_git(&path, &args, &Notify::new(), |line| {
+            progress.push(line.to_string())
+        })
+        .await
+        .unwrap();
+        assert!(!progress.is_empty());
+
+        let upstream = git(dir.path(), &["rev-parse", "--abbrev-ref", "@{u}"]);
+        assert_eq!(upstream.trim(), "origin/main");
+        assert_eq!(upstream_remote(&path).await.as_deref(), Some("origin"));
+        assert!(!push_args(&path, None, false)
+            .await
+            .contains(&"--set-upstream".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_diverged_push_pull_and_force_with_lease() {
+        let (dir, remote) = repo_with_remote();
+        let path = repo_path(&dir);
+        run(&dir, push_args(&path, None, false).await)
+            .await
+            .unwrap();
+
+        let other = clone_remote(&remote);
+        write(&other, "other.txt", "theirs\n");
+        git(other.path(), &["add", "-A"]);
+        git(other.path(), &["commit", "-q", "-m", "other change"]);
+        git(other.path(), &["push", "-q"]);
+
+        write(&dir, "f.txt", "mine\n");
+        git(dir.path(), &["commit", "-q", "-am", "my change"]);
+
+        let err = run(&dir, push_args(&path, None, false).await)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, RemoteError::NonFastForward { .. }),
+            "{:?}",
+            err
+        );
+
+        // The remote-tracking ref is stale until we fetch.
+        let err = run(&dir, push_args(&path, None, true).await)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::StaleLease { .. }), "{:?}", err);
+
+        run(&dir, fetch_args(Some("origin"), false)).await.unwrap();
+        let err = run(&dir, pull_args(None, None, Some(PullStrategy::FfOnly)))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, RemoteError::NonFastForward { .. }),
+            "{:?}",
+            err
+        );
+
+        run(&dir, pull_args(None, None, Some(PullStrategy::Rebase)))
+            .await
+            .unwrap();
+        run(&dir, push_args(&path, None, false).await)
+            .await
+            .unwrap();
+        assert!(dir.path().join("other.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_push_rejected_by_hook() {
+        let (dir, remote) = repo_with_remote();
+        let hook = remote.path().join("hooks").join("pre-receive");
+        std::fs::write(&hook, "#!/bin/sh\necho 'policy violation' >&2\nexit 1\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let err = run(&dir, push_args(&repo_path(&dir), None, false).await)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RemoteError::HookRejected { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_operation() {
+        let (dir, _remote) = repo_with_remote();
+        let operations = RemoteOperations::default();
+        let cancel = operations.start("op-1");
+        assert!(operations.cancel("op-1"));
+
+        let args = fetch_args(Some("origin"), false);
+        let result = run_remote_git(&repo_path(&dir), &args, &cancel, |_| {}).await;
+        assert_eq!(result, Err(RemoteError::Cancelled));
+
+        operations.finish("op-1");
+        assert!(!operations.cancel("op-1"));
+    }
+
+    #[tokio::test]
+    async fn test_remote_list() {
+        let (dir, remote) = repo_with_remote();
+        let remotes = git_remote_list(repo_path(&dir)).await.unwrap();
+        assert_eq!(remotes.len(), 1);
+        assert_eq!(remotes[0].name, "origin");
+        assert_eq!(remotes[0].fetch_url, remote.path().to_string_lossy());
+        assert_eq!(remotes[0].push_url, remotes[0].fetch_url);
+    }
+}
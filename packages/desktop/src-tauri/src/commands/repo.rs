@@ -0,0 +1,389 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use super::git::{parse_porcelain_v2, run_git};
+
+/// How a repository is laid out on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoKind {
+    /// A working tree with its own `.git` directory.
+    Standard,
+    /// A linked worktree of another repository.
+    Worktree,
+    /// A submodule checked out inside a superproject.
+    Submodule,
+    /// A repository without a working tree.
+    Bare,
+    /// A working tree whose `.git` file points at a git dir elsewhere, as
+    /// created by `--separate-git-dir`.
+    SeparateGitDir,
+}
+
+/// A repository rooted at a given path, as resolved by git.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoLocation {
+    pub kind: RepoKind,
+    pub git_dir: PathBuf,
+    /// Directory shared by all worktrees of the repository.
+    pub common_dir: PathBuf,
+    /// Working tree root; `None` for bare repositories.
+    pub toplevel: Option<PathBuf>,
+    /// Working tree of the superproject, for submodules.
+    pub superproject: Option<PathBuf>,
+}
+
+impl RepoLocation {
+    /// Main working tree of the repository a linked worktree belongs to.
+    pub fn main_worktree(&self) -> Option<PathBuf> {
+        if self.kind != RepoKind::Worktree {
+            return None;
+        }
+        if self
+            .common_dir
+            .file_name()
+            .is_some_and(|name| name == ".git")
+        {
+            self.common_dir.parent().map(Path::to_path_buf)
+        } else {
+            // Worktrees of a bare repository have no main working tree.
+            Some(self.common_dir.clone())
+        }
+    }
+}
+
+/// A submodule of a workspace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitSubmodule {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+    /// Commit recorded in the superproject's index.
+    pub recorded_commit: String,
+    /// Commit checked out in the submodule, if it is initialized.
+    pub commit: Option<String>,
+    pub initialized: bool,
+    /// The checked-out commit differs from the recorded one.
+    pub commit_changed: bool,
+    pub has_tracked_changes: bool,
+    pub has_untracked_changes: bool,
+}
+
+/// List the workspace's submodules with their checked-out commit and
+/// dirty state.
+#[tauri::command]
+pub async fn git_submodule_list(workspace_path: String) -> Result<Vec<GitSubmodule>, String> {
+    let staged = run_git(&workspace_path, &["ls-files", "--stage", "-z"]).await?;
+    let modules = read_gitmodules(&workspace_path).await;
+    let status = run_git(
+        &workspace_path,
+        &["status", "--porcelain=v2", "-z", "--ignore-submodules=none"],
+    )
+    .await?;
+    let status = parse_porcelain_v2(&status);
+    let states: HashMap<&str, _> = status
+        .staged
+        .iter()
+        .chain(&status.unstaged)
+        .filter_map(|change| Some((change.path.as_str(), change.submodule.as_ref()?)))
+        .collect();
+
+    let mut submodules = Vec::new();
+    for (path, recorded_commit) in parse_gitlinks(&staged) {
+        let full_path = Path::new(&workspace_path).join(&path);
+        let checked_out = match resolve_repo(&full_path).await {
+            Some(repo) if repo.kind == RepoKind::Submodule => {
+                run_git(&full_path.to_string_lossy(), &["rev-parse", "HEAD"])
+                    .await
+                    .ok()
+                    .map(|hash| hash.trim().to_string())
+            }
+            _ => None,
+        };
+        let (name, url) = match modules.get(&path) {
+            Some((name, url)) => (name.clone(), url.clone()),
+            None => (path.clone(), None),
+        };
+        let state = states.get(path.as_str());
+
+        submodules.push(GitSubmodule {
+            name,
+            url,
+            initialized: checked_out.is_some(),
+            commit_changed: checked_out
+                .as_ref()
+                .is_some_and(|commit| *commit != recorded_commit),
+            has_tracked_changes: state.is_some_and(|s| s.has_tracked_changes),
+            has_untracked_changes: state.is_some_and(|s| s.has_untracked_changes),
+            commit: checked_out,
+            recorded_commit,
+            path,
+        });
+    }
+    Ok(submodules)
+}
+
+/// Resolve the repository rooted exactly at `path` by asking git.
+///
+/// Returns `None` when `path` is not a repository root, including plain
+/// directories inside another repository's working tree.
+pub async fn resolve_repo(path: &Path) -> Option<RepoLocation> {
+    let output = rev_parse(
+        path,
+        &[
+            "--path-format=absolute",
+            "--git-dir",
+            "--git-common-dir",
+            "--is-bare-repository",
+        ],
+    )
+    .await?;
+    let mut lines = output.lines();
+    let git_dir = canonical(Path::new(lines.next()?));
+    let common_dir = canonical(Path::new(lines.next()?));
+    let is_bare = lines.next()? == "true";
+    let path = canonical(path);
+
+    if is_bare {
+        return (git_dir == path).then_some(RepoLocation {
+            kind: RepoKind::Bare,
+            git_dir,
+            common_dir,
+            toplevel: None,
+            superproject: None,
+        });
+    }
+
+    let output = rev_parse(
+        &path,
+        &["--show-toplevel", "--show-superproject-working-tree"],
+    )
+    .await?;
+    let mut lines = output.lines();
+    let toplevel = canonical(Path::new(lines.next()?));
+    if toplevel != path {
+        return None;
+    }
+    let superproject = lines.next().map(|p| canonical(Path::new(p)));
+
+    let kind = if superproject.is_some() {
+        RepoKind::Submodule
+    } else if git_dir != common_dir {
+        RepoKind::Worktree
+    } else if git_dir != toplevel.join(".git") {
+        RepoKind::SeparateGitDir
+    } else {
+        RepoKind::Standard
+    };
+
+    Some(RepoLocation {
+        kind,
+        git_dir,
+        common_dir,
+        toplevel: Some(toplevel),
+        superproject,
+    })
+}
+
+/// Run `git rev-parse` in `path`, ignoring any `GIT_DIR` the app inherited.
+async fn rev_parse(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .args(args)
+        .current_dir(path)
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Gitlink entries (mode 160000) from `git ls-files --stage -z`, as
+/// `(path, commit)` pairs.
+fn parse_gitlinks(output: &str) -> Vec<(String, String)> {
+    output
+        .split('\0')
+        .filter_map(|entry| {
+            let (info, path) = entry.split_once('\t')?;
+            let mut parts = info.split(' ');
+            if parts.next()? != "160000" {
+                return None;
+            }
+            Some((path.to_string(), parts.next()?.to_string()))
+        })
+        .collect()
+}
+
+/// Submodule names and URLs from `.gitmodules`, keyed by path.
+async fn read_gitmodules(workspace_path: &str) -> HashMap<String, (String, Option<String>)> {
+    let output = run_git(
+        workspace_path,
+        &[
+            "config",
+            "-z",
+            "--file",
+            ".gitmodules",
+            "--get-regexp",
+            r"^submodule\..*\.(path|url)$",
+        ],
+    )
+    .await
+    .unwrap_or_default();
+
+    let mut paths: HashMap<String, String> = HashMap::new();
+    let mut urls: HashMap<String, String> = HashMap::new();
+    for entry in output.split('\0') {
+        let Some((key, value)) = entry.split_once('\n') else {
+            continue;
+        };
+        let Some(key) = key.strip_prefix("submodule.") else {
+            continue;
+        };
+        if let Some(name) = key.strip_suffix(".path") {
+            paths.insert(name.to_string(), value.to_string());
+        } else if let Some(name) = key.strip_suffix(".url") {
+            urls.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    paths
+        .into_iter()
+        .map(|(name, path)| {
+            let url = urls.remove(&name);
+            (path, (name, url))
+        })
+        .collect()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path, write};
+
+    /// A superproject with `lib` added as a submodule of a separate repository.
+    fn repo_with_submodule() -> (tempfile::TempDir, tempfile::TempDir) {
+        let upstream = init_repo(&[("lib.txt", "lib\n")]);
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        git(
+            dir.path(),
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "-q",
+                &repo_path(&upstream),
+                "lib",
+            ],
+        );
+        git(dir.path(), &["commit", "-q", "-m", "add submodule"]);
+        (dir, upstream)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_repo_kinds() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let repo = resolve_repo(dir.path()).await.unwrap();
+        assert_eq!(repo.kind, RepoKind::Standard);
+        assert_eq!(repo.toplevel, Some(canonical(dir.path())));
+
+        // A plain directory inside a working tree is not a repository root.
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        assert_eq!(resolve_repo(&dir.path().join("src")).await, None);
+
+        let other = tempfile::tempdir().unwrap();
+        let worktree = other.path().join("wt");
+        git(
+            dir.path(),
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "--detach",
+                &worktree.to_string_lossy(),
+            ],
+        );
+        let repo = resolve_repo(&worktree).await.unwrap();
+        assert_eq!(repo.kind, RepoKind::Worktree);
+        assert_eq!(repo.main_worktree(), Some(canonical(dir.path())));
+
+        let bare = other.path().join("bare.git");
+        git(
+            other.path(),
+            &["init", "-q", "--bare", &bare.to_string_lossy()],
+        );
+        let repo = resolve_repo(&bare).await.unwrap();
+        assert_eq!(repo.kind, RepoKind::Bare);
+        assert_eq!(repo.toplevel, None);
+
+        let separate = other.path().join("separate");
+        let git_dir = other.path().join("separate.git");
+        git(
+            other.path(),
+            &[
+                "init",
+                "-q",
+                "--separate-git-dir",
+                &git_dir.to_string_lossy(),
+                &separate.to_string_lossy(),
+            ],
+        );
+        let repo = resolve_repo(&separate).await.unwrap();
+        assert_eq!(repo.kind, RepoKind::SeparateGitDir);
+        assert_eq!(repo.git_dir, canonical(&git_dir));
+    }
+
+    #[tokio::test]
+    async fn test_submodule_list_reports_commit_and_dirty_state() {
+        let (dir, upstream) = repo_with_submodule();
+        let repo = resolve_repo(&dir.path().join("lib")).await.unwrap();
+        assert_eq!(repo.kind, RepoKind::Submodule);
+        assert_eq!(repo.superproject, Some(canonical(dir.path())));
+
+        let submodules = git_submodule_list(repo_path(&dir)).await.unwrap();
+        assert_eq!(submodules.len(), 1);
+        let lib = &submodules[0];
+        assert_eq!(lib.name, "lib");
+        assert_eq!(lib.url.as_deref(), Some(repo_path(&upstream).as_str()));
+        assert!(lib.initialized);
+        assert_eq!(lib.commit.as_deref(), Some(lib.recorded_commit.as_str()));
+        assert!(!lib.commit_changed);
+        assert!(!lib.has_tracked_changes);
+
+        let lib_dir = dir.path().join("lib");
+        write(&dir, "lib/lib.txt", "changed\n");
+        write(&dir, "lib/new.txt", "new\n");
+        let submodules = git_submodule_list(repo_path(&dir)).await.unwrap();
+        assert!(submodules[0].has_tracked_changes);
+        assert!(submodules[0].has_untracked_changes);
+
+        git(&lib_dir, &["config", "user.name", "Test User"]);
+        git(&lib_dir, &["config", "user.email", "test@example.com"]);
+        git(&lib_dir, &["commit", "-q", "-am", "move ahead"]);
+        let submodules = git_submodule_list(repo_path(&dir)).await.unwrap();
+        assert!(submodules[0].commit_changed);
+        assert!(!submodules[0].has_tracked_changes);
+    }
+
+    #[tokio::test]
+    async fn test_submodule_list_uninitialized() {
+        let (dir, _upstream) = repo_with_submodule();
+        let clone = tempfile::tempdir().unwrap();
+        git(clone.path(), &["clone", "-q", &repo_path(&dir), "."]);
+
+        let submodules = git_submodule_list(repo_path(&clone)).await.unwrap();
+        assert_eq!(submodules.len(), 1);
+        assert!(!submodules[0].initialized);
+        assert_eq!(submodules[0].commit, None);
+    }
+}
@@ -0,0 +1,554 @@
+use serde::Serialize;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::State;
+use tokio::process::Command;
+
+use super::config::{expand_home, ProfileConfig};
+use super::config_state::ConfigState;
+use super::git::run_git;
+
+/// How long `ssh-add` may run before we give up, e.g. waiting on a passphrase.
+const SSH_ADD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of applying a profile's SSH settings to a workspace.
+#[derive(Debug, Serialize)]
+pub struct SshSetupResult {
+    /// The profile's host alias, if it has one.
+    pub ssh_host: Option<String>,
+    /// Whether the alias resolves to a real host through the SSH config.
+    pub alias_configured: bool,
+    pub remotes: Vec<RemoteRewrite>,
+    pub key: Option<SshKeyStatus>,
+    /// Value written to `core.sshCommand`, if any.
+    pub ssh_command: Option<String>,
+    /// A `core.sshCommand` from an earlier profile that was unset because
+    /// this one has no usable key.
+    pub removed_ssh_command: Option<String>,
+    pub agent: AgentStatus,
+}
+
+/// A remote whose URL was pointed at the profile's host alias.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RemoteRewrite {
+    pub name: String,
+    pub old_url: String,
+    pub new_url: String,
+}
+
+/// Checks on the profile's private key file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SshKeyStatus {
+    pub path: String,
+    pub exists: bool,
+    /// Unix permission bits, when available.
+    pub mode: Option<u32>,
+    /// False when the key is readable by group or others, which ssh rejects.
+    pub permissions_ok: bool,
+}
+
+/// Result of loading the key into ssh-agent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentStatus {
+    NotRequested,
+    /// `SSH_AUTH_SOCK` is not set.
+    NoAgent,
+    AlreadyLoaded,
+    Loaded,
+    Failed {
+        message: String,
+    },
+}
+
+/// Point a workspace's remotes and `core.sshCommand` at a profile's SSH
+/// settings, optionally loading its key into the running ssh-agent.
+#[tauri::command]
+pub async fn apply_profile_ssh(
+    config_state: State<'_, ConfigState>,
+    workspace_path: String,
+    profile_name: String,
+    load_into_agent: Option<bool>,
+) -> Result<SshSetupResult, String> {
+    let config = config_state.config()?.ok_or("Config file not found")?;
+    let profile = config
+        .profiles
+        .get(&profile_name)
+        .ok_or_else(|| format!("Profile '{}' not found in config", profile_name))?;
+
+    setup_profile_ssh(
+        &workspace_path,
+        profile,
+        load_into_agent.unwrap_or(false),
+        None,
+    )
+    .await
+}
+
+/// Apply a profile's SSH settings. `ssh_config` overrides the SSH config
+/// file used to resolve the host alias.
+pub(crate) async fn setup_profile_ssh(
+    workspace_path: &str,
+    profile: &ProfileConfig,
+    load_into_agent: bool,
+    ssh_config: Option<&Path>,
+) -> Result<SshSetupResult, String> {
+    let mut result = SshSetupResult {
+        ssh_host: profile.ssh_host.clone(),
+        alias_configured: false,
+        remotes: Vec::new(),
+        key: None,
+        ssh_command: None,
+        removed_ssh_command: None,
+        agent: AgentStatus::NotRequested,
+    };
+
+    if let Some(alias) = &profile.ssh_host {
+        let hostname = resolve_ssh_host(alias, ssh_config).await;
+        // ssh echoes unknown hosts back unchanged, so an alias resolving to
+        // itself has no config entry and rewriting to it would break remotes.
+        result.alias_configured = hostname
+            .as_deref()
+            .is_some_and(|host| !host.eq_ignore_ascii_case(alias));
+        if let (true, Some(hostname)) = (result.alias_configured, hostname) {
+            result.remotes = rewrite_remotes(workspace_path, alias, &hostname, ssh_config).await?;
+        }
+    }
+
+    if let Some(key) = &profile.ssh_key {
+        let path = expand_home(key);
+        let status = key_status(&path);
+        if status.exists && status.permissions_ok {
+            let command = ssh_command_for_key(&path);
+            run_git(
+                workspace_path,
+                &["config", "--local", "core.sshCommand", &command],
+            )
+            .await?;
+            result.ssh_command = Some(command);
+
+            if load_into_agent {
+                result.agent = load_key_into_agent(&path).await;
+            }
+        }
+        result.key = Some(status);
+    }
+
+    if result.ssh_command.is_none() {
+        result.removed_ssh_command = unset_ssh_command(workspace_path).await?;
+    }
+
+    Ok(result)
+}
+
+/// Unset `core.sshCommand` if an earlier profile set it, so its key stops
+/// being used. Commands the user wrote themselves are left alone.
+async fn unset_ssh_command(workspace_path: &str) -> Result<Option<String>, String> {
+    // `git config --get` fails when the key is not set.
+    let Ok(current) = run_git(
+        workspace_path,
+        &["config", "--local", "--get", "core.sshCommand"],
+    )
+    .await
+    else {
+        return Ok(None);
+    };
+    let current = current.trim();
+    let written_by_us = current
+        .strip_prefix("ssh -i '")
+        .is_some_and(|rest| rest.ends_with("' -o IdentitiesOnly=yes"));
+    if !written_by_us {
+        return Ok(None);
+    }
+
+    run_git(
+        workspace_path,
+        &["config", "--local", "--unset", "core.sshCommand"],
+    )
+    .await?;
+    Ok(Some(current.to_string()))
+}
+
+/// Rewrite every remote on the alias's real host to use the alias instead.
+async fn rewrite_remotes(
+    workspace_path: &str,
+    alias: &str,
+    hostname: &str,
+    ssh_config: Option<&Path>,
+) -> Result<Vec<RemoteRewrite>, String> {
+    let output = run_git(workspace_path, &["remote"]).await?;
+    let mut rewrites = Vec::new();
+
+    for name in output.lines().map(str::trim).filter(|n| !n.is_empty()) {
+        let url = run_git(workspace_path, &["remote", "get-url", name]).await?;
+        let url = url.trim();
+        let Some(parsed) = RemoteUrl::parse(url) else {
+            continue;
+        };
+        if parsed.host.eq_ignore_ascii_case(alias) {
+            continue;
+        }
+        // Other aliases for the same host (e.g. another profile's) count too.
+        let same_host = parsed.host.eq_ignore_ascii_case(hostname)
+            || resolve_ssh_host(&parsed.host, ssh_config)
+                .await
+                .is_some_and(|host| host.eq_ignore_ascii_case(hostname));
+        if !same_host {
+            continue;
+        }
+
+        let new_url = parsed.with_host(alias);
+        run_git(workspace_path, &["remote", "set-url", name, &new_url]).await?;
+        rewrites.push(RemoteRewrite {
+            name: name.to_string(),
+            old_url: url.to_string(),
+            new_url,
+        });
+    }
+
+    Ok(rewrites)
+}
+
+/// The parts of a remote URL that matter for host rewriting.
+#[derive(Debug, PartialEq)]
+struct RemoteUrl {
+    user: Option<String>,
+    host: String,
+    port: Option<String>,
+    path: String,
+}
+
+impl RemoteUrl {
+    /// Parse `ssh://`, `https://` and scp-style `user@host:path` URLs.
+    fn parse(url: &str) -> Option<Self> {
+        if let Some((scheme, rest)) = url.split_once("://") {
+            if !matches!(scheme, "ssh" | "git+ssh" | "https" | "http") {
+                return None;
+            }
+            let (authority, path) = rest.split_once('/')?;
+            let (user, host_port) = match authority.rsplit_once('@') {
+                Some((user, host)) => (Some(user), host),
+                None => (None, authority),
+            };
+            let (host, port) = match host_port.split_once(':') {
+                Some((host, port)) => (host, Some(port.to_string())),
+                None => (host_port, None),
+            };
+            let is_ssh = scheme.ends_with("ssh");
+            return Some(RemoteUrl {
+                // HTTP credentials are meaningless over SSH.
+                user: user.filter(|_| is_ssh).map(str::to_string),
+                host: host.to_string(),
+                port: port.filter(|_| is_ssh),
+                path: path.to_string(),
+            });
+        }
+
+        // scp-style: a colon before any slash, and not a local Windows path.
+        let (authority, path) = url.split_once(':')?;
+        if authority.contains('/') || authority.len() < 2 {
+            return None;
+        }
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, authority),
+        };
+        Some(RemoteUrl {
+            user,
+            host: host.to_string(),
+            port: None,
+            path: path.to_string(),
+        })
+    }
+
+    /// Render as an SSH URL on `host`, defaulting the user to `git`.
+    fn with_host(&self, host: &str) -> String {
+        let user = self.user.as_deref().unwrap_or("git");
+        match &self.port {
+            Some(port) => format!("ssh://{}@{}:{}/{}", user, host, port, self.path),
+            None => format!("{}@{}:{}", user, host, self.path),
+        }
+    }
+}
+
+/// The hostname ssh would connect to for `host`, per `ssh -G`.
+async fn resolve_ssh_host(host: &str, ssh_config: Option<&Path>) -> Option<String> {
+    let mut command = Command::new("ssh");
+    if let Some(config) = ssh_config {
+        command.arg("-F").arg(config);
+    }
+    let output = command
+        .args(["-G", host])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("hostname "))
+        .map(|host| host.trim().to_string())
+}
+
+/// Check the key exists and is private to its owner.
+fn key_status(path: &str) -> SshKeyStatus {
+    let metadata = std::fs::metadata(path).ok();
+    let mode = metadata.as_ref().and_then(permission_bits);
+
+    SshKeyStatus {
+        path: path.to_string(),
+        exists: metadata.as_ref().is_some_and(|m| m.is_file()),
+        mode,
+        permissions_ok: mode.is_none_or(|mode| mode & 0o077 == 0),
+    }
+}
+
+#[cfg(unix)]
+fn permission_bits(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn permission_bits(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// `core.sshCommand` that only offers the given key.
+fn ssh_command_for_key(path: &str) -> String {
+    format!(
+        "ssh -i '{}' -o IdentitiesOnly=yes",
+        path.replace('\'', r"'\''")
+    )
+}
+
+/// Add the key to the agent at `SSH_AUTH_SOCK` unless it is already loaded.
+async fn load_key_into_agent(path: &str) -> AgentStatus {
+    if std::env::var_os("SSH_AUTH_SOCK").is_none() {
+        return AgentStatus::NoAgent;
+    }
+
+    if let Some(fingerprint) = key_fingerprint(path).await {
+        let loaded = Command::new("ssh-add")
+            .arg("-l")
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default();
+        if loaded
+            .lines()
+            .any(|line| line.split_whitespace().nth(1) == Some(&fingerprint))
+        {
+            return AgentStatus::AlreadyLoaded;
+        }
+    }
+
+    // No stdin and a timeout: a passphrase prompt must fail rather than hang.
+    let child = Command::new("ssh-add")
+        .arg(path)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(SSH_ADD_TIMEOUT, child).await {
+        Ok(Ok(output)) if output.status.success() => AgentStatus::Loaded,
+        Ok(Ok(output)) => AgentStatus::Failed {
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        },
+        Ok(Err(e)) => AgentStatus::Failed {
+            message: format!("Failed to run ssh-add: {}", e),
+        },
+        Err(_) => AgentStatus::Failed {
+            message: "ssh-add timed out, the key may need a passphrase".to_string(),
+        },
+    }
+}
+
+/// SHA256 fingerprint of a key, as printed by `ssh-add -l`.
+async fn key_fingerprint(path: &str) -> Option<String> {
+    let output = Command::new("ssh-keygen")
+        .args(["-l", "-f", path])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path};
+
+    fn profile(ssh_host: Option<&str>, ssh_key: Option<&str>) -> ProfileConfig {
+        ProfileConfig {
+            name: "Work User".to_string(),
+            email: "work@corp.com".to_string(),
+            ssh_host: ssh_host.map(str::to_string),
+            ssh_key: ssh_key.map(str::to_string),
+            platform: None,
+            signing: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_remote_urls() {
+        assert_eq!(
+            RemoteUrl::parse("git@github.com:me/repo.git")
+                .unwrap()
+                .with_host("gh-work"),
+            "git@gh-work:me/repo.git"
+        );
+        assert_eq!(
+            RemoteUrl::parse("https://token@github.com/me/repo.git")
+                .unwrap()
+                .with_host("gh-work"),
+            "git@gh-work:me/repo.git"
+        );
+        assert_eq!(
+            RemoteUrl::parse("ssh://git@github.com:2222/me/repo.git")
+                .unwrap()
+                .with_host("gh-work"),
+            "ssh://git@gh-work:2222/me/repo.git"
+        );
+        assert_eq!(RemoteUrl::parse("/srv/git/repo.git"), None);
+        assert_eq!(RemoteUrl::parse("C:/repos/repo.git"), None);
+        assert_eq!(RemoteUrl::parse("file:///srv/git/repo.git"), None);
+    }
+
+    #[tokio::test]
+    async fn test_setup_rewrites_matching_remotes_and_sets_key() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", "git@github.com:me/repo.git"],
+        );
+        git(
+            dir.path(),
+            &[
+                "remote",
+                "add",
+                "upstream",
+                "https://github.com/org/repo.git",
+            ],
+        );
+        git(
+            dir.path(),
+            &["remote", "add", "mirror", "git@gitlab.com:me/repo.git"],
+        );
+
+        let home = tempfile::tempdir().unwrap();
+        let ssh_config = home.path().join("ssh_config");
+        std::fs::write(&ssh_config, "Host gh-work\n  HostName github.com\n").unwrap();
+        let key = home.path().join("id_work");
+        std::fs::write(&key, "not a real key\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&key, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+
+        let key_path = key.to_string_lossy().to_string();
+        let result = setup_profile_ssh(
+            &repo_path(&dir),
+            &profile(Some("gh-work"), Some(&key_path)),
+            false,
+            Some(&ssh_config),
+        )
+        .await
+        .unwrap();
+
+        assert!(result.alias_configured);
+        let names: Vec<_> = result.remotes.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["origin", "upstream"]);
+        assert_eq!(
+            git(dir.path(), &["remote", "get-url", "upstream"]).trim(),
+            "git@gh-work:org/repo.git"
+        );
+        assert_eq!(
+            git(dir.path(), &["remote", "get-url", "mirror"]).trim(),
+            "git@gitlab.com:me/repo.git"
+        );
+
+        assert!(result.key.unwrap().permissions_ok);
+        let command = git(dir.path(), &["config", "core.sshCommand"]);
+        assert_eq!(command.trim(), result.ssh_command.unwrap());
+        assert_eq!(result.agent, AgentStatus::NotRequested);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_setup_skips_unsafe_key_and_unknown_alias() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", "git@github.com:me/repo.git"],
+        );
+        let home = tempfile::tempdir().unwrap();
+        let ssh_config = home.path().join("ssh_config");
+        std::fs::write(&ssh_config, "").unwrap();
+        let key = home.path().join("id_work");
+        std::fs::write(&key, "not a real key\n").unwrap();
+        std::fs::set_permissions(&key, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let key_path = key.to_string_lossy().to_string();
+        let result = setup_profile_ssh(
+            &repo_path(&dir),
+            &profile(Some("gh-missing"), Some(&key_path)),
+            true,
+            Some(&ssh_config),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.alias_configured);
+        assert!(result.remotes.is_empty());
+        let key = result.key.unwrap();
+        assert_eq!(key.mode, Some(0o644));
+        assert!(!key.permissions_ok);
+        assert_eq!(result.ssh_command, None);
+        assert_eq!(result.agent, AgentStatus::NotRequested);
+    }
+
+    #[tokio::test]
+    async fn test_setup_unsets_previous_profile_ssh_command() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let previous = ssh_command_for_key("/home/me/.ssh/id_personal");
+        git(
+            dir.path(),
+            &["config", "--local", "core.sshCommand", &previous],
+        );
+
+        let result = setup_profile_ssh(&repo_path(&dir), &profile(None, None), false, None)
+            .await
+            .unwrap();
+        assert_eq!(result.removed_ssh_command, Some(previous));
+        let config = std::fs::read_to_string(dir.path().join(".git/config")).unwrap();
+        assert!(!config.contains("sshCommand"));
+
+        // A command the user set by hand stays.
+        git(
+            dir.path(),
+            &["config", "--local", "core.sshCommand", "ssh -F ~/.ssh/alt"],
+        );
+        let result = setup_profile_ssh(&repo_path(&dir), &profile(None, None), false, None)
+            .await
+            .unwrap();
+        assert_eq!(result.removed_ssh_command, None);
+        let command = git(dir.path(), &["config", "core.sshCommand"]);
+        assert_eq!(command.trim(), "ssh -F ~/.ssh/alt");
+    }
+}
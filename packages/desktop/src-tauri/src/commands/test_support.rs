@@ -0,0 +1,58 @@
+//! Helpers shared by command tests that need a real git repository.
+
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Run git in `dir`, panicking on failure, and return stdout.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Create a repository on `main` with the given files committed and a local identity.
+pub fn init_repo(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    git(dir.path(), &["config", "user.name", "Test User"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    git(dir.path(), &["config", "commit.gpgsign", "false"]);
+    for (name, content) in files {
+        write(&dir, name, content);
+    }
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-q", "-m", "initial"]);
+    dir
+}
+
+pub fn repo_path(dir: &TempDir) -> String {
+    dir.path().to_string_lossy().to_string()
+}
+
+/// Write a file relative to the repository root, creating parent directories.
+pub fn write(dir: &TempDir, name: &str, content: &str) {
+    let path = dir.path().join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    std::fs::write(path, content).unwrap();
+}
+
+pub fn read(dir: &TempDir, name: &str) -> String {
+    std::fs::read_to_string(dir.path().join(name)).unwrap()
+}
+
+/// Content of a file as stored in the index.
+pub fn read_index(dir: &TempDir, name: &str) -> String {
+    git(dir.path(), &["show", &format!(":{}", name)])
+}
@@ -0,0 +1,457 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::git::{parse_porcelain_v2, run_git, run_git_with_input, GitStatus};
+use super::repo::{resolve_repo, RepoKind, RepoLocation};
+
+/// Event emitted when files in a watched workspace change.
+pub const WORKSPACE_CHANGED_EVENT: &str = "workspace:changed";
+
+/// Event emitted with the workspace's git status after a change.
+pub const STATUS_EVENT: &str = "git:status";
+
+/// Quiet period after the last filesystem event before a batch is handled.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Longest a batch is held back while events keep arriving.
+const MAX_BATCH_AGE: Duration = Duration::from_secs(2);
+
+/// Dependency and build output directories never watched, even when they
+/// are not ignored.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Payload for [`WORKSPACE_CHANGED_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceChanged {
+    pub workspace_path: String,
+    /// Changed working tree paths relative to the workspace, excluding
+    /// ignored files.
+    pub paths: Vec<String>,
+    /// HEAD, the index or a ref changed.
+    pub git_changed: bool,
+}
+
+/// Payload for [`STATUS_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceStatus {
+    pub workspace_path: String,
+    pub status: GitStatus,
+}
+
+/// Watched workspaces by the path they were subscribed with.
+#[derive(Default)]
+pub struct WorkspaceWatchers {
+    watching: Mutex<HashMap<String, WorkspaceWatch>>,
+}
+
+/// A running watch; dropping it stops the task, which owns the watcher.
+pub(crate) struct WorkspaceWatch {
+    task: JoinHandle<()>,
+}
+
+impl Drop for WorkspaceWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// One debounced batch of changes.
+pub(crate) struct WatchUpdate {
+    pub changed: WorkspaceChanged,
+    /// Fresh status, absent if git could not compute it.
+    pub status: Option<GitStatus>,
+}
+
+/// Start pushing change and status events for a workspace.
+///
+/// Subscribing again to the same path replaces the previous watch.
+#[tauri::command]
+pub async fn watch_workspace(
+    app: AppHandle,
+    watchers: State<'_, WorkspaceWatchers>,
+    workspace_path: String,
+) -> Result<(), String> {
+    let watch = spawn_watch(&workspace_path, move |update| {
+        let workspace_path = update.changed.workspace_path.clone();
+        let _ = app.emit(WORKSPACE_CHANGED_EVENT, update.changed);
+        if let Some(status) = update.status {
+            let _ = app.emit(
+                STATUS_EVENT,
+                WorkspaceStatus {
+                    workspace_path,
+                    status,
+                },
+            );
+        }
+    })
+    .await?;
+
+    if let Ok(mut watching) = watchers.watching.lock() {
+        watching.insert(workspace_path, watch);
+    }
+    Ok(())
+}
+
+/// Stop watching a workspace. Returns false if it was not watched.
+#[tauri::command]
+pub fn unwatch_workspace(watchers: State<'_, WorkspaceWatchers>, workspace_path: String) -> bool {
+    match watchers.watching.lock() {
+        Ok(mut watching) => watching.remove(&workspace_path).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Watch a workspace, calling `on_update` once per debounced batch.
+pub(crate) async fn spawn_watch<F>(
+    workspace_path: &str,
+    on_update: F,
+) -> Result<WorkspaceWatch, String>
+where
+    F: Fn(WatchUpdate) + Send + 'static,
+{
+    let repo = match resolve_repo(Path::new(workspace_path)).await {
+        Some(repo) if repo.kind != RepoKind::Bare => repo,
+        _ => return Err("Not a git repository".to_string()),
+    };
+    let target = WatchTarget::new(workspace_path, repo)?;
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            let _ = sender.send(path);
+        }
+    })
+    .map_err(|e| format!("Failed to start watcher: {}", e))?;
+
+    let dirs = target.watched_dirs(vec![target.root.clone()]).await;
+    let watches = dirs
+        .iter()
+        .map(|dir| (dir.clone(), RecursiveMode::NonRecursive))
+        .chain(target.git_paths());
+    for (path, mode) in watches {
+        watcher
+            .watch(&path, mode)
+            .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+    }
+
+    let watched = dirs.into_iter().collect();
+    let task = tokio::spawn(debounce(target, watcher, watched, receiver, on_update));
+    Ok(WorkspaceWatch { task })
+}
+
+/// Collect events until they go quiet for [`DEBOUNCE`], or the batch is
+/// [`MAX_BATCH_AGE`] old, then report the batch. Directories created in
+/// the meantime are watched before it is reported.
+async fn debounce<F>(
+    target: WatchTarget,
+    mut watcher: RecommendedWatcher,
+    mut watched: HashSet<PathBuf>,
+    mut receiver: mpsc::UnboundedReceiver<PathBuf>,
+    on_update: F,
+) where
+    F: Fn(WatchUpdate),
+{
+    while let Some(first) = receiver.recv().await {
+        let flush_at = Instant::now() + MAX_BATCH_AGE;
+        let mut batch = vec![first];
+        loop {
+            let quiet = DEBOUNCE.min(flush_at.saturating_duration_since(Instant::now()));
+            match tokio::time::timeout(quiet, receiver.recv()).await {
+                Ok(Some(path)) => batch.push(path),
+                _ => break,
+            }
+        }
+
+        watched.retain(|dir| dir.is_dir());
+        let created: Vec<PathBuf> = batch
+            .iter()
+            .filter(|path| {
+                matches!(target.classify(path), Change::WorkTree(_))
+                    && path.is_dir()
+                    && !watched.contains(*path)
+                    && !is_skipped_dir(path)
+            })
+            .cloned()
+            .collect();
+        if !created.is_empty() {
+            for dir in target.watched_dirs(created).await {
+                if watcher.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+                    watched.insert(dir);
+                }
+            }
+        }
+
+        if let Some(update) = target.process(batch).await {
+            on_update(update);
+        }
+    }
+}
+
+/// What a classified filesystem event touched.
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// HEAD, the index or refs.
+    Git,
+    /// A working tree path relative to the root.
+    WorkTree(String),
+    /// Other git internals, or paths outside the workspace.
+    Skip,
+}
+
+/// Locations watched for one workspace.
+struct WatchTarget {
+    workspace_path: String,
+    root: PathBuf,
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+}
+
+impl WatchTarget {
+    fn new(workspace_path: &str, repo: RepoLocation) -> Result<Self, String> {
+        let root = repo.toplevel.ok_or("Not a git repository")?;
+        Ok(WatchTarget {
+            workspace_path: workspace_path.to_string(),
+            root,
+            git_dir: repo.git_dir,
+            common_dir: repo.common_dir,
+        })
+    }
+
+    /// The parts of the git directories that affect status: HEAD and the
+    /// index at their top level, and the refs below the common dir.
+    fn git_paths(&self) -> Vec<(PathBuf, RecursiveMode)> {
+        let mut paths = vec![(self.git_dir.clone(), RecursiveMode::NonRecursive)];
+        if self.common_dir != self.git_dir {
+            paths.push((self.common_dir.clone(), RecursiveMode::NonRecursive));
+        }
+        paths.push((self.common_dir.join("refs"), RecursiveMode::Recursive));
+        paths
+    }
+
+    /// `dirs` and the directories below them worth watching one by one:
+    /// everything except `.git`, directories git ignores and
+    /// [`SKIPPED_DIRS`]. Symlinks are not followed.
+    async fn watched_dirs(&self, mut pending: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        while !pending.is_empty() {
+            let kept = self.drop_ignored(pending).await;
+            pending = Vec::new();
+            for dir in &kept {
+                let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+                    continue;
+                };
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let is_dir = entry.file_type().await.is_ok_and(|t| t.is_dir());
+                    let path = entry.path();
+                    if is_dir && !is_skipped_dir(&path) {
+                        pending.push(path);
+                    }
+                }
+            }
+            dirs.extend(kept);
+        }
+        dirs
+    }
+
+    /// Remove the directories git ignores.
+    async fn drop_ignored(&self, dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+        // A trailing slash lets directory-only patterns such as `build/`
+        // match.
+        let relative = |dir: &Path| {
+            dir.strip_prefix(&self.root)
+                .ok()
+                .filter(|r| !r.as_os_str().is_empty())
+                .map(|r| format!("{}/", r.to_string_lossy().replace('\\', "/")))
+        };
+        let input: String = dirs
+            .iter()
+            .filter_map(|dir| relative(dir))
+            .map(|p| format!("{}\0", p))
+            .collect();
+        if input.is_empty() {
+            return dirs;
+        }
+        let root = self.root.to_string_lossy().to_string();
+        // Exits non-zero when nothing is ignored.
+        let ignored: HashSet<String> =
+            match run_git_with_input(&root, &["check-ignore", "-z", "--stdin"], &input).await {
+                Ok(output) => output.split('\0').map(str::to_string).collect(),
+                Err(_) => HashSet::new(),
+            };
+        dirs.into_iter()
+            .filter(|dir| relative(dir).is_none_or(|r| !ignored.contains(&r)))
+            .collect()
+    }
+
+    fn classify(&self, path: &Path) -> Change {
+        for dir in [&self.git_dir, &self.common_dir] {
+            if let Ok(relative) = path.strip_prefix(dir) {
+                return if is_watched_git_path(relative) {
+                    Change::Git
+                } else {
+                    Change::Skip
+                };
+            }
+        }
+        match path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => {
+                Change::WorkTree(relative.to_string_lossy().replace('\\', "/"))
+            }
+            _ => Change::Skip,
+        }
+    }
+
+    /// Turn a batch of raw paths into an update, or `None` if nothing
+    /// relevant changed.
+    async fn process(&self, batch: Vec<PathBuf>) -> Option<WatchUpdate> {
+        let mut git_changed = false;
+        let mut paths = BTreeSet::new();
+        for path in &batch {
+            match self.classify(path) {
+                Change::Git => git_changed = true,
+                Change::WorkTree(relative) => {
+                    paths.insert(relative);
+                }
+                Change::Skip => {}
+            }
+        }
+
+        let root = self.root.to_string_lossy().to_string();
+        if !paths.is_empty() {
+            let input: String = paths.iter().map(|p| format!("{}\0", p)).collect();
+            // Exits non-zero when nothing is ignored.
+            if let Ok(ignored) =
+                run_git_with_input(&root, &["check-ignore", "-z", "--stdin"], &input).await
+            {
+                for path in ignored.split('\0') {
+                    paths.remove(path);
+                }
+            }
+        }
+        if paths.is_empty() && !git_changed {
+            return None;
+        }
+
+        // Without optional locks status does not rewrite the index, which
+        // would trigger another event.
+        let status = run_git(
+            &root,
+            &[
+                "--no-optional-locks",
+                "status",
+                "--porcelain=v2",
+                "--branch",
+                "-z",
+            ],
+        )
+        .await
+        .ok()
+        .map(|output| parse_porcelain_v2(&output));
+
+        Some(WatchUpdate {
+            changed: WorkspaceChanged {
+                workspace_path: self.workspace_path.clone(),
+                paths: paths.into_iter().collect(),
+                git_changed,
+            },
+            status,
+        })
+    }
+}
+
+/// `.git` or one of [`SKIPPED_DIRS`], judged by name.
+fn is_skipped_dir(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == ".git" || SKIPPED_DIRS.iter().any(|skip| name == *skip))
+}
+
+/// Git internals whose changes affect status: HEAD, the index and refs.
+fn is_watched_git_path(relative: &Path) -> bool {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    matches!(relative.as_str(), "HEAD" | "index" | "packed-refs" | "refs")
+        || relative.starts_with("refs/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path, write};
+
+    #[test]
+    fn test_watched_git_paths() {
+        assert!(is_watched_git_path(Path::new("HEAD")));
+        assert!(is_watched_git_path(Path::new("index")));
+        assert!(is_watched_git_path(Path::new("refs/heads/main")));
+        assert!(is_watched_git_path(Path::new("packed-refs")));
+        assert!(!is_watched_git_path(Path::new("index.lock")));
+        assert!(!is_watched_git_path(Path::new("objects/ab/cdef")));
+        assert!(!is_watched_git_path(Path::new("logs/HEAD")));
+    }
+
+    #[tokio::test]
+    async fn test_watched_dirs_skip_git_and_ignored_trees() {
+        let dir = init_repo(&[(".gitignore", "build/\n"), ("src/lib/a.rs", "\n")]);
+        write(&dir, "build/out/app", "\n");
+        write(&dir, "node_modules/dep/index.js", "\n");
+        let repo = resolve_repo(dir.path()).await.unwrap();
+        let target = WatchTarget::new(&repo_path(&dir), repo).unwrap();
+
+        let mut dirs: Vec<String> = target
+            .watched_dirs(vec![target.root.clone()])
+            .await
+            .iter()
+            .map(|d| {
+                d.strip_prefix(&target.root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        dirs.sort();
+        assert_eq!(dirs, ["", "src", "src/lib"]);
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_changes_with_status() {
+        let dir = init_repo(&[("a.txt", "a\n"), (".gitignore", "*.log\n")]);
+        let (sender, mut updates) = mpsc::unbounded_channel();
+        let _watch = spawn_watch(&repo_path(&dir), move |update| {
+            let _ = sender.send(update);
+        })
+        .await
+        .unwrap();
+
+        write(&dir, "debug.log", "noise\n");
+        write(&dir, "a.txt", "changed\n");
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.changed.paths, ["a.txt"]);
+        let status = update.status.unwrap();
+        assert_eq!(status.unstaged.len(), 1);
+        assert_eq!(status.unstaged[0].path, "a.txt");
+
+        git(dir.path(), &["commit", "-qam", "change"]);
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(update.changed.git_changed);
+        assert!(update.status.unwrap().unstaged.is_empty());
+    }
+}
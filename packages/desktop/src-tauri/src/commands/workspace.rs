@@ -1,9 +1,59 @@
-use serde::Serialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, State, Window};
 use tokio::process::Command as AsyncCommand;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+
+use super::config::{
+    expand_home, find_workspace_config, DitLoopConfigFile, ProfileConfig, WorkspaceConfig,
+};
+use super::config_state::ConfigState;
+use super::filesystem::detect_language;
+use super::git::{parse_porcelain_v2, run_git};
+use super::repo::{resolve_repo, RepoKind, RepoLocation};
+use super::workspace_settings::{resolve_settings, DEFAULT_EDITOR};
+
+/// Event emitted for every repository found by `scan_workspaces`.
+pub const WORKSPACE_FOUND_EVENT: &str = "workspace:found";
+
+/// How many levels below the base path a scan looks by default.
+const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Directory patterns skipped by default: dependency and build output
+/// trees, and hidden directories such as caches.
+const DEFAULT_IGNORE: &[&str] = &["node_modules", "target", "vendor", ".*"];
+
+/// Directories read, or repositories inspected, at the same time.
+const SCAN_CONCURRENCY: usize = 16;
+
+/// Languages reported per workspace, most files first.
+const MAX_LANGUAGES: usize = 5;
+
+/// Files at a project root that identify how it is built, in reporting order.
+const PROJECT_MARKERS: &[(&str, ProjectKind)] = &[
+    ("Cargo.toml", ProjectKind::Cargo),
+    ("pnpm-lock.yaml", ProjectKind::Pnpm),
+    ("pnpm-workspace.yaml", ProjectKind::Pnpm),
+    ("yarn.lock", ProjectKind::Yarn),
+    ("bun.lockb", ProjectKind::Bun),
+    ("bun.lock", ProjectKind::Bun),
+    ("package-lock.json", ProjectKind::Npm),
+    ("pyproject.toml", ProjectKind::Python),
+    ("setup.py", ProjectKind::Python),
+    ("requirements.txt", ProjectKind::Python),
+    ("go.mod", ProjectKind::Go),
+    ("pom.xml", ProjectKind::Maven),
+    ("build.gradle", ProjectKind::Gradle),
+    ("build.gradle.kts", ProjectKind::Gradle),
+    ("Gemfile", ProjectKind::Bundler),
+    ("Package.swift", ProjectKind::Swift),
+];
 
 /// Workspace information detected from filesystem.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct WorkspaceInfo {
     pub name: String,
     pub path: String,
@@ -11,54 +61,447 @@ pub struct WorkspaceInfo {
     pub has_aidf: bool,
     pub git_branch: Option<String>,
     pub last_modified: Option<u64>,
+    /// Repository layout, when the path is a repository root.
+    pub repo_kind: Option<RepoKind>,
+    /// Git directory as resolved by git, which may live outside the path.
+    pub git_dir: Option<String>,
+    /// Linked worktree of another repository.
+    pub is_worktree: bool,
+    /// Main working tree of the repository a linked worktree belongs to.
+    pub main_worktree: Option<String>,
+    /// Build tools detected at the workspace root.
+    pub project_kinds: Vec<ProjectKind>,
+    /// Tracked files per language, most common first.
+    pub languages: Vec<LanguageCount>,
+    /// Files with staged, unstaged, untracked or conflicted changes.
+    pub dirty_count: usize,
+    /// Upstream tracking branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    /// Committer time of HEAD in seconds since the epoch.
+    pub last_commit_time: Option<u64>,
+    /// Fetch URL of `origin`, or of the first remote when there is none.
+    pub remote_url: Option<String>,
+}
+
+/// Build tool or package manager a project uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Cargo,
+    Pnpm,
+    Yarn,
+    Bun,
+    Npm,
+    Python,
+    Go,
+    Maven,
+    Gradle,
+    Bundler,
+    Swift,
+}
+
+/// Number of tracked files in one language.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub files: usize,
+}
+
+/// Limits for a workspace scan.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScanOptions {
+    /// Levels below the base path to search; direct children are depth 1.
+    pub max_depth: Option<usize>,
+    /// Glob patterns for directories to skip. A pattern without `/` matches
+    /// the directory name, otherwise the path relative to the base. `*`
+    /// does not cross `/`, `**` does. Replaces the defaults when given.
+    pub ignore: Option<Vec<String>>,
+}
+
+/// A configured workspace, with a group's repositories as children.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceNode {
+    pub name: String,
+    pub path: String,
+    /// `single` or `group`.
+    pub r#type: String,
+    /// Profile of the entry, inherited by a group's repositories.
+    pub profile: String,
+    pub aidf: bool,
+    /// The path exists on disk.
+    pub exists: bool,
+    /// Details when the path is a repository.
+    pub info: Option<WorkspaceInfo>,
+    pub children: Vec<WorkspaceNode>,
 }
 
-/// Scan a directory for git repos with optional .ai/ folders.
+/// Configured workspaces together with repositories found next to them.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceRegistry {
+    /// Configured entries, each followed by its group's repositories.
+    pub workspaces: Vec<RegisteredWorkspace>,
+    /// Discovered repositories not covered by any configured entry.
+    pub suggestions: Vec<WorkspaceInfo>,
+}
+
+/// A workspace known from config, with its profile resolved.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredWorkspace {
+    pub name: String,
+    pub path: String,
+    /// `single` or `group`.
+    pub r#type: String,
+    /// Name of the group a discovered repository belongs to.
+    pub group: Option<String>,
+    pub profile_name: String,
+    /// The named profile, absent when config does not define it.
+    pub profile: Option<ProfileConfig>,
+    /// The configured path no longer exists.
+    pub missing: bool,
+    pub info: Option<WorkspaceInfo>,
+}
+
+/// Payload for [`WORKSPACE_FOUND_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceFound {
+    pub scan_id: String,
+    pub workspace: WorkspaceInfo,
+}
+
+/// Scan a directory tree for git repos with optional .ai/ folders.
+///
+/// Repositories are not descended into, so nested repositories and
+/// submodules are left to the repository's own views.
 #[tauri::command]
-pub async fn detect_workspaces(base_path: String) -> Result<Vec<WorkspaceInfo>, String> {
+pub async fn detect_workspaces(
+    base_path: String,
+    options: Option<ScanOptions>,
+) -> Result<Vec<WorkspaceInfo>, String> {
     let base = PathBuf::from(&base_path);
     if !base.is_dir() {
         return Err(format!("Not a directory: {}", base_path));
     }
 
+    let mut found = spawn_scan(base, options.unwrap_or_default());
     let mut workspaces = Vec::new();
-    let mut entries = tokio::fs::read_dir(&base).await.map_err(|e| e.to_string())?;
+    while let Some(workspace) = found.recv().await {
+        workspaces.push(workspace);
+    }
+
+    workspaces.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    Ok(workspaces)
+}
+
+/// Scan like `detect_workspaces`, emitting each repository as it is found.
+///
+/// Returns the number of workspaces found once the scan is complete.
+#[tauri::command]
+pub async fn scan_workspaces(
+    window: Window,
+    scan_id: String,
+    base_path: String,
+    options: Option<ScanOptions>,
+) -> Result<usize, String> {
+    let base = PathBuf::from(&base_path);
+    if !base.is_dir() {
+        return Err(format!("Not a directory: {}", base_path));
+    }
+
+    let mut found = spawn_scan(base, options.unwrap_or_default());
+    let mut count = 0;
+    while let Some(workspace) = found.recv().await {
+        count += 1;
+        let _ = window.emit(
+            WORKSPACE_FOUND_EVENT,
+            WorkspaceFound {
+                scan_id: scan_id.clone(),
+                workspace,
+            },
+        );
+    }
+    Ok(count)
+}
 
-    while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+/// Shared state of one scan.
+struct Scan {
+    base: PathBuf,
+    max_depth: usize,
+    ignore: Vec<String>,
+    /// Canonical directories already visited, so symlink loops and
+    /// symlinked duplicates are walked once.
+    visited: Mutex<HashSet<PathBuf>>,
+    permits: Semaphore,
+    found: mpsc::UnboundedSender<WorkspaceInfo>,
+}
+
+/// Start walking `base` in the background, sending each repository found.
+///
+/// The channel closes when the walk is complete.
+pub(crate) fn spawn_scan(
+    base: PathBuf,
+    options: ScanOptions,
+) -> mpsc::UnboundedReceiver<WorkspaceInfo> {
+    let (found, receiver) = mpsc::unbounded_channel();
+    let scan = Arc::new(Scan {
+        base: base.clone(),
+        max_depth: options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+        ignore: options
+            .ignore
+            .unwrap_or_else(|| DEFAULT_IGNORE.iter().map(|p| p.to_string()).collect()),
+        visited: Mutex::new(HashSet::new()),
+        permits: Semaphore::new(SCAN_CONCURRENCY),
+        found,
+    });
+
+    tokio::spawn(async move {
+        let mut tasks = JoinSet::new();
+        tasks.spawn(visit(scan.clone(), base, 0));
+        while let Some(result) = tasks.join_next().await {
+            for (dir, depth) in result.unwrap_or_default() {
+                tasks.spawn(visit(scan.clone(), dir, depth));
+            }
+        }
+    });
+    receiver
+}
+
+/// Visit one directory: report it if it is a repository, otherwise return
+/// the subdirectories still to walk. The base itself is only walked, so a
+/// base that is a repository still lists the repositories under it.
+async fn visit(scan: Arc<Scan>, dir: PathBuf, depth: usize) -> Vec<(PathBuf, usize)> {
+    let Ok(_permit) = scan.permits.acquire().await else {
+        return Vec::new();
+    };
+    let Ok(canonical) = tokio::fs::canonicalize(&dir).await else {
+        return Vec::new();
+    };
+    if !scan.visited.lock().unwrap().insert(canonical) {
+        return Vec::new();
+    }
+
+    if depth > 0 && looks_like_repo(&dir) {
+        if let Some(repo) = resolve_repo(&dir).await {
+            let _ = scan.found.send(workspace_info(&dir, Some(repo)).await);
+            return Vec::new();
+        }
+    }
+    if depth >= scan.max_depth {
+        return Vec::new();
+    }
+
+    let mut children = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+        return children;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
         let path = entry.path();
-        if !path.is_dir() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" || !path.is_dir() {
+            continue;
+        }
+        let relative = path
+            .strip_prefix(&scan.base)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| name.clone());
+        if matches_any(&scan.ignore, &name, &relative) {
             continue;
         }
+        children.push((path, depth + 1));
+    }
+    children
+}
 
-        let has_git = path.join(".git").exists();
-        if !has_git {
+/// Cheap check before asking git: a `.git` entry, or the layout of a bare
+/// repository.
+fn looks_like_repo(path: &Path) -> bool {
+    path.join(".git").exists() || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+}
+
+fn matches_any(patterns: &[String], name: &str, relative: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+            glob_match(pattern.trim_matches('/').as_bytes(), relative.as_bytes())
+        } else {
+            glob_match(pattern.as_bytes(), name.as_bytes())
+        }
+    })
+}
+
+/// Match `text` against a glob supporting `*`, `**` and `?`.
+pub(crate) fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            text.first().is_some_and(|&c| c != b'/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Resolve the configured workspaces into a tree, expanding each group to
+/// the repositories under its path.
+#[tauri::command]
+pub async fn get_workspace_tree(
+    config_state: State<'_, ConfigState>,
+) -> Result<Vec<WorkspaceNode>, String> {
+    let config = config_state.config()?.unwrap_or_default();
+    Ok(build_workspace_tree(&config.workspaces).await)
+}
+
+pub(crate) async fn build_workspace_tree(workspaces: &[WorkspaceConfig]) -> Vec<WorkspaceNode> {
+    let mut nodes = Vec::with_capacity(workspaces.len());
+    for ws in workspaces {
+        let path = PathBuf::from(&ws.path);
+        let exists = path.is_dir();
+        let mut node = WorkspaceNode {
+            name: ws.name.clone(),
+            path: ws.path.clone(),
+            r#type: ws.r#type.clone(),
+            profile: ws.profile.clone(),
+            aidf: ws.aidf,
+            exists,
+            info: None,
+            children: Vec::new(),
+        };
+        if exists && ws.is_group() {
+            node.children = resolve_group(ws).await;
+        } else if exists {
+            let repo = resolve_repo(&path).await;
+            node.info = Some(workspace_info(&path, repo).await);
+        }
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Repositories of a group entry, named by their path relative to the group.
+///
+/// `exclude` patterns are skipped on top of the scan defaults; with
+/// `include` patterns only matching repositories are kept.
+async fn resolve_group(group: &WorkspaceConfig) -> Vec<WorkspaceNode> {
+    let base = PathBuf::from(&group.path);
+    let ignore = DEFAULT_IGNORE
+        .iter()
+        .map(|p| p.to_string())
+        .chain(group.exclude.iter().cloned())
+        .collect();
+    let options = ScanOptions {
+        max_depth: None,
+        ignore: Some(ignore),
+    };
+
+    let mut found = spawn_scan(base.clone(), options);
+    let mut children = Vec::new();
+    while let Some(info) = found.recv().await {
+        let path = PathBuf::from(&info.path);
+        let relative = path
+            .strip_prefix(&base)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| info.name.clone());
+        if !group.include.is_empty() && !matches_any(&group.include, &info.name, &relative) {
             continue;
         }
+        children.push(WorkspaceNode {
+            name: relative,
+            path: info.path.clone(),
+            r#type: "single".to_string(),
+            profile: group.profile.clone(),
+            aidf: group.aidf || info.has_aidf,
+            exists: true,
+            info: Some(info),
+            children: Vec::new(),
+        });
+    }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    children
+}
 
-        let has_aidf = path.join(".ai").exists();
-        let git_branch = get_current_branch(&path).await;
-        let last_modified = tokio::fs::metadata(&path)
-            .await
-            .ok()
-            .and_then(|m| m.modified().ok())
-            .and_then(|t| {
-                t.duration_since(std::time::SystemTime::UNIX_EPOCH)
-                    .ok()
-                    .map(|d| d.as_secs())
-            });
+/// Merge configured workspaces with the repositories found by scanning.
+///
+/// `base_paths` defaults to the parent directories of the configured
+/// single workspaces, so sibling repositories are suggested.
+#[tauri::command]
+pub async fn get_workspace_registry(
+    config_state: State<'_, ConfigState>,
+    base_paths: Option<Vec<String>>,
+    options: Option<ScanOptions>,
+) -> Result<WorkspaceRegistry, String> {
+    let config = config_state.config()?.unwrap_or_default();
+    Ok(build_registry(&config, base_paths, options.unwrap_or_default()).await)
+}
 
-        workspaces.push(WorkspaceInfo {
-            name: entry.file_name().to_string_lossy().to_string(),
-            path: path.to_string_lossy().to_string(),
-            has_git,
-            has_aidf,
-            git_branch,
-            last_modified,
+pub(crate) async fn build_registry(
+    config: &DitLoopConfigFile,
+    base_paths: Option<Vec<String>>,
+    options: ScanOptions,
+) -> WorkspaceRegistry {
+    let mut workspaces = Vec::new();
+    for node in build_workspace_tree(&config.workspaces).await {
+        let profile = config.profiles.get(&node.profile).cloned();
+        workspaces.push(RegisteredWorkspace {
+            name: node.name.clone(),
+            path: node.path,
+            r#type: node.r#type,
+            group: None,
+            profile_name: node.profile,
+            profile: profile.clone(),
+            missing: !node.exists,
+            info: node.info,
         });
+        for child in node.children {
+            workspaces.push(RegisteredWorkspace {
+                name: child.name,
+                path: child.path,
+                r#type: child.r#type,
+                group: Some(node.name.clone()),
+                profile_name: child.profile,
+                profile: profile.clone(),
+                missing: false,
+                info: child.info,
+            });
+        }
     }
 
-    workspaces.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-    Ok(workspaces)
+    let base_paths = base_paths.unwrap_or_else(|| {
+        config
+            .workspaces
+            .iter()
+            .filter(|ws| !ws.is_group())
+            .filter_map(|ws| Path::new(&ws.path).parent())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    });
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+    for base in base_paths {
+        let base = PathBuf::from(expand_home(&base));
+        if !base.is_dir() {
+            continue;
+        }
+        let mut found = spawn_scan(base, options.clone());
+        while let Some(info) = found.recv().await {
+            let path = PathBuf::from(&info.path);
+            let canonical = std::fs::canonicalize(&path).unwrap_or(path);
+            if find_workspace_config(config, &canonical).is_none() && seen.insert(canonical) {
+                suggestions.push(info);
+            }
+        }
+    }
+    suggestions.sort_by(|a, b| a.path.cmp(&b.path));
+
+    WorkspaceRegistry {
+        workspaces,
+        suggestions,
+    }
 }
 
 /// Get detailed workspace info for a single path.
@@ -69,10 +512,17 @@ pub async fn get_workspace_info(path: String) -> Result<WorkspaceInfo, String> {
         return Err(format!("Not a directory: {}", path));
     }
 
-    let has_git = ws_path.join(".git").exists();
-    let has_aidf = ws_path.join(".ai").exists();
-    let git_branch = get_current_branch(&ws_path).await;
-    let last_modified = tokio::fs::metadata(&ws_path)
+    let repo = resolve_repo(&ws_path).await;
+    let mut info = workspace_info(&ws_path, repo).await;
+    info.path = path;
+    Ok(info)
+}
+
+/// Gather workspace details, running the git queries concurrently.
+async fn workspace_info(path: &Path, repo: Option<RepoLocation>) -> WorkspaceInfo {
+    let has_aidf = path.join(".ai").exists();
+    let main_worktree = repo.as_ref().and_then(|r| r.main_worktree());
+    let last_modified = tokio::fs::metadata(path)
         .await
         .ok()
         .and_then(|m| m.modified().ok())
@@ -82,17 +532,124 @@ pub async fn get_workspace_info(path: String) -> Result<WorkspaceInfo, String> {
                 .map(|d| d.as_secs())
         });
 
-    Ok(WorkspaceInfo {
-        name: ws_path
+    let mut info = WorkspaceInfo {
+        name: path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| path.clone()),
-        path,
-        has_git,
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        path: path.to_string_lossy().to_string(),
+        has_git: repo.is_some(),
         has_aidf,
-        git_branch,
+        git_branch: None,
         last_modified,
-    })
+        repo_kind: repo.as_ref().map(|r| r.kind),
+        git_dir: repo
+            .as_ref()
+            .map(|r| r.git_dir.to_string_lossy().to_string()),
+        is_worktree: repo.as_ref().is_some_and(|r| r.kind == RepoKind::Worktree),
+        main_worktree: main_worktree.map(|p| p.to_string_lossy().to_string()),
+        project_kinds: detect_project_kinds(path),
+        languages: Vec::new(),
+        dirty_count: 0,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        last_commit_time: None,
+        remote_url: None,
+    };
+    if repo.is_none() {
+        return info;
+    }
+
+    // Status and ls-files fail in a bare repository, leaving the defaults.
+    let repo_path = info.path.clone();
+    let (branch, status, files, last_commit, remote_url) = tokio::join!(
+        get_current_branch(path),
+        run_git(&repo_path, &["status", "--porcelain=v2", "--branch", "-z"]),
+        run_git(&repo_path, &["ls-files", "-z"]),
+        run_git(&repo_path, &["log", "-1", "--format=%ct"]),
+        get_remote_url(&repo_path),
+    );
+    info.git_branch = branch;
+    if let Ok(output) = status {
+        let status = parse_porcelain_v2(&output);
+        let dirty: HashSet<&str> = status
+            .staged
+            .iter()
+            .chain(&status.unstaged)
+            .map(|c| c.path.as_str())
+            .chain(status.untracked.iter().map(String::as_str))
+            .chain(status.conflicted.iter().map(|c| c.path.as_str()))
+            .collect();
+        info.dirty_count = dirty.len();
+        info.upstream = status.upstream.clone();
+        info.ahead = status.ahead;
+        info.behind = status.behind;
+    }
+    if let Ok(files) = files {
+        info.languages = count_languages(files.split('\0').filter(|f| !f.is_empty()));
+    }
+    info.last_commit_time = last_commit.ok().and_then(|t| t.trim().parse().ok());
+    info.remote_url = remote_url;
+    info
+}
+
+/// Project kinds whose marker files exist at `path`.
+///
+/// A `package.json` without a lockfile counts as npm.
+fn detect_project_kinds(path: &Path) -> Vec<ProjectKind> {
+    let mut kinds = Vec::new();
+    for (marker, kind) in PROJECT_MARKERS {
+        if !kinds.contains(kind) && path.join(marker).is_file() {
+            kinds.push(*kind);
+        }
+    }
+    let has_js_kind = kinds.iter().any(|k| {
+        matches!(
+            k,
+            ProjectKind::Pnpm | ProjectKind::Yarn | ProjectKind::Bun | ProjectKind::Npm
+        )
+    });
+    if !has_js_kind && path.join("package.json").is_file() {
+        kinds.push(ProjectKind::Npm);
+    }
+    kinds
+}
+
+/// Count files per language, skipping files with no recognised language.
+fn count_languages<'a>(files: impl Iterator<Item = &'a str>) -> Vec<LanguageCount> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for file in files {
+        let language = detect_language(&PathBuf::from(file));
+        if language != "plaintext" {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+
+    let mut languages: Vec<LanguageCount> = counts
+        .into_iter()
+        .map(|(language, files)| LanguageCount { language, files })
+        .collect();
+    languages.sort_by(|a, b| {
+        b.files
+            .cmp(&a.files)
+            .then_with(|| a.language.cmp(&b.language))
+    });
+    languages.truncate(MAX_LANGUAGES);
+    languages
+}
+
+/// Fetch URL of `origin`, falling back to the first configured remote.
+async fn get_remote_url(workspace_path: &str) -> Option<String> {
+    let remotes = run_git(workspace_path, &["remote"]).await.ok()?;
+    let remote = remotes
+        .lines()
+        .find(|r| *r == "origin")
+        .or_else(|| remotes.lines().next())?;
+    run_git(workspace_path, &["remote", "get-url", remote])
+        .await
+        .ok()
+        .map(|url| url.trim().to_string())
 }
 
 /// Open a terminal at the given path.
@@ -137,10 +694,24 @@ pub async fn open_in_terminal(path: String) -> Result<(), String> {
     Ok(())
 }
 
-/// Open a path in the default code editor.
+/// Open a path in a code editor.
+///
+/// Without `editor`, the workspace's resolved editor setting is used.
 #[tauri::command]
-pub async fn open_in_editor(path: String, editor: Option<String>) -> Result<(), String> {
-    let cmd = editor.unwrap_or_else(|| "code".to_string());
+pub async fn open_in_editor(
+    config_state: State<'_, ConfigState>,
+    path: String,
+    editor: Option<String>,
+) -> Result<(), String> {
+    let cmd = match editor {
+        Some(editor) => editor,
+        None => {
+            let config = config_state.config()?.unwrap_or_default();
+            resolve_settings(&config, Path::new(&path))
+                .editor
+                .map_or_else(|| DEFAULT_EDITOR.to_string(), |editor| editor.value)
+        }
+    };
     AsyncCommand::new(&cmd)
         .arg(&path)
         .spawn()
@@ -149,7 +720,7 @@ pub async fn open_in_editor(path: String, editor: Option<String>) -> Result<(),
 }
 
 /// Get the current git branch for a repository path.
-async fn get_current_branch(path: &PathBuf) -> Option<String> {
+async fn get_current_branch(path: &Path) -> Option<String> {
     AsyncCommand::new("git")
         .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .current_dir(path)
@@ -164,3 +735,203 @@ async fn get_current_branch(path: &PathBuf) -> Option<String> {
             }
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path, write};
+
+    fn init_at(path: &Path) {
+        std::fs::create_dir_all(path).unwrap();
+        git(path, &["init", "-q", "-b", "main"]);
+    }
+
+    fn names(workspaces: &[WorkspaceInfo]) -> Vec<String> {
+        let mut names: Vec<String> = workspaces.iter().map(|w| w.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_count_languages() {
+        let files = [
+            "src/main.rs",
+            "src/lib.rs",
+            "web/app.ts",
+            "Makefile",
+            "README.md",
+        ];
+        let languages = count_languages(files.into_iter());
+        assert_eq!(
+            languages[0],
+            LanguageCount {
+                language: "rust".into(),
+                files: 2
+            }
+        );
+        assert_eq!(languages.len(), 3);
+        assert!(languages.iter().all(|l| l.language != "plaintext"));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_info_details() {
+        let dir = init_repo(&[
+            ("Cargo.toml", "[package]\n"),
+            ("package.json", "{}\n"),
+            ("src/main.rs", "fn main() {}\n"),
+        ]);
+        git(
+            dir.path(),
+            &["remote", "add", "origin", "https://example.com/repo.git"],
+        );
+        write(&dir, "src/main.rs", "fn main() { }\n");
+        write(&dir, "notes.md", "new\n");
+
+        let info = get_workspace_info(repo_path(&dir)).await.unwrap();
+        assert_eq!(info.project_kinds, [ProjectKind::Cargo, ProjectKind::Npm]);
+        assert_eq!(info.languages[0].language, "json");
+        assert_eq!(info.dirty_count, 2);
+        assert_eq!(info.git_branch.as_deref(), Some("main"));
+        assert!(info.last_commit_time.is_some());
+        assert_eq!(
+            info.remote_url.as_deref(),
+            Some("https://example.com/repo.git")
+        );
+    }
+
+    fn group(path: &Path, include: &[&str], exclude: &[&str]) -> WorkspaceConfig {
+        WorkspaceConfig {
+            name: "clients".into(),
+            path: path.to_string_lossy().to_string(),
+            r#type: "group".into(),
+            profile: "work".into(),
+            aidf: false,
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+            settings: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workspace_tree_expands_groups() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        init_at(&base.join("acme/api"));
+        init_at(&base.join("acme/legacy-app"));
+        init_at(&base.join("globex/web"));
+
+        let mut missing = group(&base.join("missing"), &[], &[]);
+        missing.r#type = "single".into();
+        let tree = build_workspace_tree(&[group(base, &[], &["legacy-*"]), missing]).await;
+        assert_eq!(tree.len(), 2);
+        let names: Vec<&str> = tree[0].children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["acme/api", "globex/web"]);
+        assert!(tree[0].children.iter().all(|c| c.profile == "work"));
+        assert!(!tree[1].exists);
+        assert!(tree[1].info.is_none());
+
+        let tree = build_workspace_tree(&[group(base, &["acme/*"], &[])]).await;
+        let names: Vec<&str> = tree[0].children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["acme/api", "acme/legacy-app"]);
+    }
+
+    #[tokio::test]
+    async fn test_registry_merges_config_and_discovery() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        init_at(&base.join("projects/api"));
+        init_at(&base.join("projects/new-tool"));
+        init_at(&base.join("clients/acme"));
+
+        let yaml = format!(
+            "profiles:\n  work:\n    name: Work User\n    email: work@example.com\nworkspaces:\n  - name: api\n    path: {root}/projects/api\n    profile: work\n  - name: gone\n    path: {root}/projects/gone\n    profile: personal\n  - name: clients\n    path: {root}/clients\n    type: group\n    profile: work\n",
+            root = base.to_string_lossy()
+        );
+        let config: DitLoopConfigFile = serde_yaml::from_str(&yaml).unwrap();
+        let registry = build_registry(&config, None, ScanOptions::default()).await;
+
+        let names: Vec<&str> = registry
+            .workspaces
+            .iter()
+            .map(|w| w.name.as_str())
+            .collect();
+        assert_eq!(names, ["api", "gone", "clients", "acme"]);
+        assert_eq!(
+            registry.workspaces[0].profile.as_ref().unwrap().email,
+            "work@example.com"
+        );
+        assert!(registry.workspaces[1].missing);
+        assert!(registry.workspaces[1].profile.is_none());
+        assert_eq!(registry.workspaces[3].group.as_deref(), Some("clients"));
+        assert_eq!(registry.workspaces[3].profile_name, "work");
+
+        let suggested: Vec<&str> = registry
+            .suggestions
+            .iter()
+            .map(|w| w.name.as_str())
+            .collect();
+        assert_eq!(suggested, ["new-tool"]);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"node_modules", b"node_modules"));
+        assert!(glob_match(b".*", b".cache"));
+        assert!(!glob_match(b".*", b"src"));
+        assert!(glob_match(b"build-?", b"build-1"));
+        assert!(glob_match(b"clients/*/archive", b"clients/acme/archive"));
+        assert!(!glob_match(b"clients/*", b"clients/acme/archive"));
+        assert!(glob_match(b"**/archive", b"clients/acme/archive"));
+        assert!(glob_match(b"**/archive", b"archive"));
+    }
+
+    #[tokio::test]
+    async fn test_detect_nested_workspaces() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        init_at(&base.join("acme/api"));
+        init_at(&base.join("acme/api/packages/inner"));
+        init_at(&base.join("acme/web"));
+        init_at(&base.join("acme/web/node_modules/dep"));
+        init_at(&base.join("solo"));
+        init_at(&base.join("solo/libs/core"));
+        init_at(&base.join("deep/a/b/c/too-deep"));
+
+        let found = detect_workspaces(base.to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(names(&found), ["api", "solo", "web"]);
+
+        let found = detect_workspaces(base.join("solo").to_string_lossy().to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(names(&found), ["core"]);
+
+        let options = ScanOptions {
+            max_depth: Some(5),
+            ignore: Some(vec!["acme/*".into()]),
+        };
+        let found = detect_workspaces(base.to_string_lossy().to_string(), Some(options))
+            .await
+            .unwrap();
+        assert_eq!(names(&found), ["solo", "too-deep"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detect_survives_symlink_loop() {
+        let root = tempfile::tempdir().unwrap();
+        let base = root.path();
+        init_at(&base.join("group/repo"));
+        std::os::unix::fs::symlink(base, base.join("group/loop")).unwrap();
+
+        let options = ScanOptions {
+            max_depth: Some(10),
+            ignore: Some(Vec::new()),
+        };
+        let found = detect_workspaces(base.to_string_lossy().to_string(), Some(options))
+            .await
+            .unwrap();
+        assert_eq!(names(&found), ["repo"]);
+    }
+}
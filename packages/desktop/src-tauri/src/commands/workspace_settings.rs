@@ -0,0 +1,475 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::config::{find_workspace_config, DitLoopConfigFile, WorkspaceSettings};
+use super::config_state::ConfigState;
+use super::git::run_git;
+use super::workspace::glob_match;
+
+/// Settings file committed at the root of a workspace.
+pub const REPO_SETTINGS_FILE: &str = ".ditloop.yml";
+
+/// Editor used when no layer sets one.
+pub const DEFAULT_EDITOR: &str = "code";
+
+/// Where a setting came from, listed from highest to lowest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingSource {
+    /// `.ditloop.yml` in the workspace.
+    RepoFile,
+    /// The `ditloop` section of the workspace's `.ai/config.yml`.
+    AidfConfig,
+    /// The workspace's entry in the user's config file.
+    UserConfig,
+    /// Built-in default.
+    Default,
+}
+
+/// One source considered while resolving.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsLayer {
+    pub source: SettingSource,
+    /// File read for this layer; absent for defaults.
+    pub path: Option<String>,
+    /// The layer exists and was used.
+    pub found: bool,
+    /// Why the layer was skipped, e.g. a file that doesn't parse.
+    pub error: Option<String>,
+    /// Keys the file sets that an untrusted repository isn't allowed to.
+    pub ignored_keys: Vec<String>,
+}
+
+/// A resolved value and the layer it was taken from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: SettingSource,
+}
+
+/// Effective settings for a workspace. Each field comes whole from the
+/// highest layer that sets it, except the two that protect the user: the
+/// user's email domain outranks repository files, and protected branches
+/// are combined from every layer.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedWorkspaceSettings {
+    pub workspace_path: String,
+    /// Name of the configured workspace the path belongs to, if any.
+    pub workspace: Option<String>,
+    /// The path is covered by `trustedRepos`, so its files may set commands.
+    pub trusted: bool,
+    /// Every layer, from highest to lowest precedence.
+    pub layers: Vec<SettingsLayer>,
+    pub email_domain: Option<Resolved<String>>,
+    pub ai_tool: Option<Resolved<String>>,
+    pub ai_args: Option<Resolved<Vec<String>>>,
+    /// Patterns from every layer, so a repository can add to the user's
+    /// list but not empty it.
+    pub protected_branches: Vec<Resolved<String>>,
+    pub editor: Option<Resolved<String>>,
+}
+
+/// Shape of `.ai/config.yml` as far as DitLoop is concerned.
+#[derive(Deserialize)]
+struct AidfConfig {
+    #[serde(default)]
+    ditloop: Option<WorkspaceSettings>,
+}
+
+/// Resolve a workspace's settings, reporting where each one comes from.
+#[tauri::command]
+pub fn resolve_workspace_settings(
+    config_state: State<'_, ConfigState>,
+    workspace_path: String,
+) -> Result<ResolvedWorkspaceSettings, String> {
+    let config = config_state.config()?.unwrap_or_default();
+    Ok(resolve_settings(&config, Path::new(&workspace_path)))
+}
+
+/// Merge the repository's settings files over the user's workspace entry.
+pub(crate) fn resolve_settings(
+    config: &DitLoopConfigFile,
+    workspace_path: &Path,
+) -> ResolvedWorkspaceSettings {
+    let workspace = find_workspace_config(config, workspace_path);
+
+    let repo_path = workspace_path.join(REPO_SETTINGS_FILE);
+    let (repo, mut repo_layer) = read_layer(SettingSource::RepoFile, &repo_path, |text| {
+        serde_yaml::from_str::<Option<WorkspaceSettings>>(text).map(Option::unwrap_or_default)
+    });
+    let aidf_path = workspace_path.join(".ai").join("config.yml");
+    let (aidf, mut aidf_layer) = read_layer(SettingSource::AidfConfig, &aidf_path, |text| {
+        serde_yaml::from_str::<Option<AidfConfig>>(text)
+            .map(|config| config.and_then(|c| c.ditloop).unwrap_or_default())
+    });
+    let trusted = is_trusted(config, workspace_path);
+    let (repo, aidf) = if trusted {
+        (repo, aidf)
+    } else {
+        (
+            repo.map(|settings| data_only(settings, &mut repo_layer)),
+            aidf.map(|settings| data_only(settings, &mut aidf_layer)),
+        )
+    };
+    let defaults = WorkspaceSettings {
+        editor: Some(DEFAULT_EDITOR.to_string()),
+        ..Default::default()
+    };
+
+    let layers = [
+        (SettingSource::RepoFile, repo.unwrap_or_default()),
+        (SettingSource::AidfConfig, aidf.unwrap_or_default()),
+        (
+            SettingSource::UserConfig,
+            workspace.map(|ws| ws.settings.clone()).unwrap_or_default(),
+        ),
+        (SettingSource::Default, defaults),
+    ];
+
+    ResolvedWorkspaceSettings {
+        workspace_path: workspace_path.to_string_lossy().to_string(),
+        workspace: workspace.map(|ws| ws.name.clone()),
+        trusted,
+        layers: vec![
+            repo_layer,
+            aidf_layer,
+            SettingsLayer {
+                source: SettingSource::UserConfig,
+                path: None,
+                found: workspace.is_some(),
+                error: None,
+                ignored_keys: Vec::new(),
+            },
+            SettingsLayer {
+                source: SettingSource::Default,
+                path: None,
+                found: true,
+                error: None,
+                ignored_keys: Vec::new(),
+            },
+        ],
+        email_domain: pick(&layers[2..], |s| s.email_domain.as_ref())
+            .or_else(|| pick(&layers, |s| s.email_domain.as_ref())),
+        ai_tool: pick(&layers, |s| s.ai_tool.as_ref()),
+        ai_args: pick(&layers, |s| s.ai_args.as_ref()),
+        protected_branches: union(&layers, |s| s.protected_branches.as_ref()),
+        editor: pick(&layers, |s| s.editor.as_ref()),
+    }
+}
+
+/// Whether the user listed the path, or a directory above it, in
+/// `trustedRepos`.
+fn is_trusted(config: &DitLoopConfigFile, workspace_path: &Path) -> bool {
+    let path = fs::canonicalize(workspace_path).unwrap_or_else(|_| workspace_path.to_path_buf());
+    config.trusted_repos.iter().any(|trusted| {
+        let trusted = PathBuf::from(trusted);
+        let trusted = fs::canonicalize(&trusted).unwrap_or(trusted);
+        path.starts_with(trusted)
+    })
+}
+
+/// Keep the settings any repository may set, noting the rest on its layer.
+///
+/// The editor and the AI tool with its arguments are commands the app
+/// runs, so an untrusted repository can't set them; otherwise opening a
+/// cloned repository could run code it ships.
+fn data_only(settings: WorkspaceSettings, layer: &mut SettingsLayer) -> WorkspaceSettings {
+    let commands = [
+        ("editor", settings.editor.is_some()),
+        ("aiTool", settings.ai_tool.is_some()),
+        ("aiArgs", settings.ai_args.is_some()),
+    ];
+    layer.ignored_keys = commands
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(key, _)| key.to_string())
+        .collect();
+    WorkspaceSettings {
+        email_domain: settings.email_domain,
+        protected_branches: settings.protected_branches,
+        ..Default::default()
+    }
+}
+
+/// The value from the first layer that sets the field.
+fn pick<T: Clone>(
+    layers: &[(SettingSource, WorkspaceSettings)],
+    field: impl Fn(&WorkspaceSettings) -> Option<&T>,
+) -> Option<Resolved<T>> {
+    layers.iter().find_map(|(source, settings)| {
+        field(settings).map(|value| Resolved {
+            value: value.clone(),
+            source: *source,
+        })
+    })
+}
+
+/// The list entries of every layer that sets the field, without repeats.
+fn union(
+    layers: &[(SettingSource, WorkspaceSettings)],
+    field: impl Fn(&WorkspaceSettings) -> Option<&Vec<String>>,
+) -> Vec<Resolved<String>> {
+    let mut values: Vec<Resolved<String>> = Vec::new();
+    for (source, settings) in layers {
+        for value in field(settings).into_iter().flatten() {
+            if !values.iter().any(|seen| &seen.value == value) {
+                values.push(Resolved {
+                    value: value.clone(),
+                    source: *source,
+                });
+            }
+        }
+    }
+    values
+}
+
+/// Read one settings file. A missing file is an unused layer; one that
+/// doesn't parse is skipped with the error reported.
+fn read_layer(
+    source: SettingSource,
+    path: &Path,
+    parse: impl Fn(&str) -> Result<WorkspaceSettings, serde_yaml::Error>,
+) -> (Option<WorkspaceSettings>, SettingsLayer) {
+    let mut layer = SettingsLayer {
+        source,
+        path: Some(path.to_string_lossy().to_string()),
+        found: false,
+        error: None,
+        ignored_keys: Vec::new(),
+    };
+    let settings = match fs::read_to_string(path) {
+        Ok(text) => match parse(&text) {
+            Ok(settings) => {
+                layer.found = true;
+                Some(settings)
+            }
+            Err(e) => {
+                layer.error = Some(format!("Failed to parse {}: {}", path.display(), e));
+                None
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            layer.error = Some(format!("Failed to read {}: {}", path.display(), e));
+            None
+        }
+    };
+    (settings, layer)
+}
+
+/// Whether `email` belongs to `domain`, ignoring case.
+pub(crate) fn email_in_domain(email: &str, domain: &str) -> bool {
+    let domain = domain.trim_start_matches('@');
+    email
+        .rsplit_once('@')
+        .is_some_and(|(_, email_domain)| email_domain.eq_ignore_ascii_case(domain))
+}
+
+/// The current branch, if the workspace's `protectedBranches` cover it.
+pub(crate) async fn protected_branch(
+    config: &DitLoopConfigFile,
+    workspace_path: &str,
+) -> Option<String> {
+    let patterns = resolve_settings(config, Path::new(workspace_path)).protected_branches;
+    if patterns.is_empty() {
+        return None;
+    }
+    let branch = run_git(workspace_path, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .await
+        .ok()?;
+    let branch = branch.trim();
+    patterns
+        .iter()
+        .any(|pattern| glob_match(pattern.value.as_bytes(), branch.as_bytes()))
+        .then(|| branch.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::WorkspaceConfig;
+    use crate::commands::test_support::{git, init_repo, repo_path};
+
+    fn config_for(path: &Path) -> DitLoopConfigFile {
+        let yaml = format!(
+            "workspaces:\n  - name: api\n    path: {}\n    editor: vim\n    aiTool: aider\n    protectedBranches: [main]\n",
+            path.display()
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_workspace_settings() {
+        let config = config_for(Path::new("/tmp/api"));
+        let ws: &WorkspaceConfig = &config.workspaces[0];
+        assert_eq!(ws.settings.editor.as_deref(), Some("vim"));
+        assert_eq!(
+            ws.settings.protected_branches,
+            Some(vec!["main".to_string()])
+        );
+        assert_eq!(ws.settings.email_domain, None);
+    }
+
+    #[test]
+    fn test_repo_files_override_user_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config_for(dir.path());
+        fs::write(
+            dir.path().join(REPO_SETTINGS_FILE),
+            "emailDomain: company.com\nprotectedBranches: [main, release/*]\neditor: ./evil.sh\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join(".ai")).unwrap();
+        fs::write(
+            dir.path().join(".ai").join("config.yml"),
+            "project: api\nditloop:\n  emailDomain: ignored.com\n  aiTool: ./run.sh\n  aiArgs: [--verbose]\n",
+        )
+        .unwrap();
+
+        let resolved = resolve_settings(&config, dir.path());
+        assert_eq!(resolved.workspace.as_deref(), Some("api"));
+        assert!(resolved.layers.iter().all(|l| l.error.is_none()));
+        assert_eq!(
+            resolved.email_domain,
+            Some(Resolved {
+                value: "company.com".to_string(),
+                source: SettingSource::RepoFile,
+            })
+        );
+        let patterns: Vec<_> = resolved
+            .protected_branches
+            .iter()
+            .map(|p| p.value.as_str())
+            .collect();
+        assert_eq!(patterns, ["main", "release/*"]);
+        // Commands are only taken from the user's config.
+        assert_eq!(resolved.layers[0].ignored_keys, ["editor"]);
+        assert_eq!(resolved.layers[1].ignored_keys, ["aiTool", "aiArgs"]);
+        let ai_tool = resolved.ai_tool.unwrap();
+        assert_eq!(
+            (ai_tool.value.as_str(), ai_tool.source),
+            ("aider", SettingSource::UserConfig)
+        );
+        assert_eq!(resolved.ai_args, None);
+        let editor = resolved.editor.unwrap();
+        assert_eq!(
+            (editor.value.as_str(), editor.source),
+            ("vim", SettingSource::UserConfig)
+        );
+    }
+
+    #[test]
+    fn test_repo_files_cannot_lift_user_protections() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "workspaces:\n  - name: api\n    path: {}\n    emailDomain: corp.com\n    protectedBranches: [main]\n",
+            dir.path().display()
+        );
+        let config: DitLoopConfigFile = serde_yaml::from_str(&yaml).unwrap();
+        fs::write(
+            dir.path().join(REPO_SETTINGS_FILE),
+            "emailDomain: gmail.com\nprotectedBranches: []\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join(".ai")).unwrap();
+        fs::write(
+            dir.path().join(".ai").join("config.yml"),
+            "ditloop:\n  protectedBranches: [release/*, main]\n",
+        )
+        .unwrap();
+
+        let resolved = resolve_settings(&config, dir.path());
+        assert_eq!(
+            resolved.email_domain,
+            Some(Resolved {
+                value: "corp.com".to_string(),
+                source: SettingSource::UserConfig,
+            })
+        );
+        assert_eq!(
+            resolved.protected_branches,
+            [
+                Resolved {
+                    value: "release/*".to_string(),
+                    source: SettingSource::AidfConfig,
+                },
+                Resolved {
+                    value: "main".to_string(),
+                    source: SettingSource::AidfConfig,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trusted_repo_files_set_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = config_for(dir.path());
+        fs::write(
+            dir.path().join(REPO_SETTINGS_FILE),
+            "editor: ./tools/edit.sh\naiTool: claude\naiArgs: [--verbose]\n",
+        )
+        .unwrap();
+        assert!(!resolve_settings(&config, dir.path()).trusted);
+
+        config.trusted_repos = vec![dir.path().to_string_lossy().to_string()];
+        let resolved = resolve_settings(&config, dir.path());
+        assert!(resolved.trusted);
+        assert!(resolved.layers[0].ignored_keys.is_empty());
+        assert_eq!(
+            resolved.ai_tool,
+            Some(Resolved {
+                value: "claude".to_string(),
+                source: SettingSource::RepoFile,
+            })
+        );
+        assert_eq!(resolved.ai_args.unwrap().value, ["--verbose"]);
+        assert_eq!(resolved.editor.unwrap().value, "./tools/edit.sh");
+
+        // A sibling that only shares a name prefix isn't covered.
+        config.trusted_repos = vec![format!("{}-other", dir.path().display())];
+        assert!(!resolve_settings(&config, dir.path()).trusted);
+    }
+
+    #[test]
+    fn test_invalid_repo_file_is_reported_and_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(REPO_SETTINGS_FILE), "editor: [unclosed\n").unwrap();
+
+        let resolved = resolve_settings(&DitLoopConfigFile::default(), dir.path());
+        assert!(!resolved.layers[0].found);
+        assert!(resolved.layers[0].error.is_some());
+        assert!(!resolved.layers[1].found);
+        let editor = resolved.editor.unwrap();
+        assert_eq!(
+            (editor.value.as_str(), editor.source),
+            (DEFAULT_EDITOR, SettingSource::Default)
+        );
+        assert_eq!(resolved.email_domain, None);
+    }
+
+    #[tokio::test]
+    async fn test_protected_branch_matches_patterns() {
+        let dir = init_repo(&[(REPO_SETTINGS_FILE, "protectedBranches: [release/*]\n")]);
+        let config = config_for(dir.path());
+        git(dir.path(), &["checkout", "-qb", "release/1.0"]);
+        assert_eq!(
+            protected_branch(&config, &repo_path(&dir)).await.as_deref(),
+            Some("release/1.0")
+        );
+        git(dir.path(), &["checkout", "-qb", "feature"]);
+        assert_eq!(protected_branch(&config, &repo_path(&dir)).await, None);
+    }
+
+    #[test]
+    fn test_email_in_domain() {
+        assert!(email_in_domain("dev@Company.com", "company.com"));
+        assert!(email_in_domain("dev@company.com", "@company.com"));
+        assert!(!email_in_domain("dev@sub.company.com", "company.com"));
+        assert!(!email_in_domain("dev", "company.com"));
+    }
+}
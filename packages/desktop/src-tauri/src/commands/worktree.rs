@@ -0,0 +1,269 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::git::run_git;
+
+/// A working tree attached to a repository.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GitWorktree {
+    pub path: String,
+    /// Commit checked out, absent for a bare main worktree.
+    pub head: Option<String>,
+    /// Short branch name, or `None` when detached.
+    pub branch: Option<String>,
+    /// The repository's own working tree rather than a linked one.
+    pub is_main: bool,
+    pub is_bare: bool,
+    pub is_detached: bool,
+    pub locked: bool,
+    pub lock_reason: Option<String>,
+    /// The worktree directory is gone and `git_worktree_prune` would remove it.
+    pub prunable: bool,
+}
+
+/// List the workspace's worktrees, main worktree first.
+#[tauri::command]
+pub async fn git_worktree_list(workspace_path: String) -> Result<Vec<GitWorktree>, String> {
+    let output = run_git(&workspace_path, &["worktree", "list", "--porcelain", "-z"]).await?;
+    Ok(parse_worktree_list(&output))
+}
+
+/// Add a linked worktree at `path` with `branch` checked out.
+///
+/// With `create_branch` the branch is created from `start_point` (or HEAD)
+/// first, which is the usual way to give an AI session its own branch.
+#[tauri::command]
+pub async fn git_worktree_add(
+    workspace_path: String,
+    path: String,
+    branch: String,
+    create_branch: bool,
+    start_point: Option<String>,
+) -> Result<GitWorktree, String> {
+    if branch.starts_with('-') || start_point.as_deref().is_some_and(|s| s.starts_with('-')) {
+        return Err(format!("Invalid branch name '{}'", branch));
+    }
+
+    let mut args = vec!["worktree", "add"];
+    if create_branch {
+        args.extend(["-b", branch.as_str(), "--", path.as_str()]);
+        if let Some(start) = &start_point {
+            args.push(start);
+        }
+    } else {
+        args.extend(["--", path.as_str(), branch.as_str()]);
+    }
+    run_git(&workspace_path, &args).await?;
+
+    let target = canonical(Path::new(&path));
+    git_worktree_list(workspace_path)
+        .await?
+        .into_iter()
+        .find(|wt| canonical(Path::new(&wt.path)) == target)
+        .ok_or_else(|| format!("Worktree at '{}' was not created", path))
+}
+
+/// Lock a worktree so it is not pruned or removed, e.g. while an agent runs.
+#[tauri::command]
+pub async fn git_worktree_lock(
+    workspace_path: String,
+    path: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    let mut args = vec!["worktree", "lock"];
+    if let Some(reason) = &reason {
+        args.extend(["--reason", reason.as_str()]);
+    }
+    args.extend(["--", path.as_str()]);
+    run_git(&workspace_path, &args).await.map(|_| ())
+}
+
+/// Unlock a worktree.
+#[tauri::command]
+pub async fn git_worktree_unlock(workspace_path: String, path: String) -> Result<(), String> {
+    run_git(&workspace_path, &["worktree", "unlock", "--", &path])
+        .await
+        .map(|_| ())
+}
+
+/// Prune administrative data for worktrees whose directories are gone.
+///
+/// Returns the paths that were pruned; locked worktrees are kept.
+#[tauri::command]
+pub async fn git_worktree_prune(workspace_path: String) -> Result<Vec<String>, String> {
+    let prunable: Vec<String> = git_worktree_list(workspace_path.clone())
+        .await?
+        .into_iter()
+        .filter(|wt| wt.prunable && !wt.locked)
+        .map(|wt| wt.path)
+        .collect();
+    run_git(&workspace_path, &["worktree", "prune"]).await?;
+    Ok(prunable)
+}
+
+/// Remove a linked worktree. `force` discards its local changes.
+#[tauri::command]
+pub async fn git_worktree_remove(
+    workspace_path: String,
+    path: String,
+    force: bool,
+) -> Result<(), String> {
+    let mut args = vec!["worktree", "remove"];
+    if force {
+        args.push("--force");
+    }
+    args.extend(["--", path.as_str()]);
+    run_git(&workspace_path, &args).await.map(|_| ())
+}
+
+/// Find one of the workspace's worktrees by path or branch name.
+pub(crate) async fn resolve_worktree(
+    workspace_path: &str,
+    worktree: &str,
+) -> Result<String, String> {
+    let target = canonical(Path::new(worktree));
+    git_worktree_list(workspace_path.to_string())
+        .await?
+        .into_iter()
+        .find(|wt| {
+            wt.branch.as_deref() == Some(worktree) || canonical(Path::new(&wt.path)) == target
+        })
+        .map(|wt| wt.path)
+        .ok_or_else(|| format!("No worktree '{}' in {}", worktree, workspace_path))
+}
+
+/// Parse `git worktree list --porcelain -z` output.
+///
+/// Each worktree is a run of `key value` fields ended by an empty field.
+pub fn parse_worktree_list(output: &str) -> Vec<GitWorktree> {
+    let mut worktrees: Vec<GitWorktree> = Vec::new();
+    let mut current: Option<GitWorktree> = None;
+
+    for field in output.split('\0') {
+        if field.is_empty() {
+            worktrees.extend(current.take());
+            continue;
+        }
+        let (key, value) = field.split_once(' ').unwrap_or((field, ""));
+        if key == "worktree" {
+            worktrees.extend(current.take());
+            current = Some(GitWorktree {
+                path: value.to_string(),
+                head: None,
+                branch: None,
+                is_main: worktrees.is_empty(),
+                is_bare: false,
+                is_detached: false,
+                locked: false,
+                lock_reason: None,
+                prunable: false,
+            });
+            continue;
+        }
+        let Some(wt) = current.as_mut() else {
+            continue;
+        };
+        match key {
+            "HEAD" => wt.head = Some(value.to_string()),
+            "branch" => {
+                wt.branch = Some(
+                    value
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(value)
+                        .to_string(),
+                )
+            }
+            "bare" => wt.is_bare = true,
+            "detached" => wt.is_detached = true,
+            "locked" => {
+                wt.locked = true;
+                wt.lock_reason = Some(value.to_string()).filter(|r| !r.is_empty());
+            }
+            "prunable" => wt.prunable = true,
+            _ => {}
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_support::{git, init_repo, repo_path};
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "worktree /repo\0HEAD abc\0branch refs/heads/main\0\0worktree /repo-agent\0HEAD abc\0branch refs/heads/agent/one\0locked agent busy\0\0worktree /gone\0HEAD def\0detached\0prunable gitdir file points to non-existent location\0\0";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 3);
+        assert!(worktrees[0].is_main);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert!(!worktrees[1].is_main);
+        assert_eq!(worktrees[1].branch.as_deref(), Some("agent/one"));
+        assert!(worktrees[1].locked);
+        assert_eq!(worktrees[1].lock_reason.as_deref(), Some("agent busy"));
+        assert!(worktrees[2].is_detached);
+        assert!(worktrees[2].prunable);
+        assert_eq!(worktrees[2].branch, None);
+    }
+
+    #[tokio::test]
+    async fn test_add_lock_and_remove_worktree() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+        let parent = tempfile::tempdir().unwrap();
+        let wt_path = parent.path().join("agent one");
+        let wt_str = wt_path.to_string_lossy().to_string();
+
+        let wt = git_worktree_add(path.clone(), wt_str.clone(), "agent".into(), true, None)
+            .await
+            .unwrap();
+        assert_eq!(wt.branch.as_deref(), Some("agent"));
+        assert!(!wt.is_main);
+
+        git_worktree_lock(path.clone(), wt_str.clone(), Some("agent busy".into()))
+            .await
+            .unwrap();
+        assert!(git_worktree_remove(path.clone(), wt_str.clone(), false)
+            .await
+            .is_err());
+        git_worktree_unlock(path.clone(), wt_str.clone())
+            .await
+            .unwrap();
+        let resolved = resolve_worktree(&path, "agent").await.unwrap();
+        assert_eq!(canonical(Path::new(&resolved)), canonical(&wt_path));
+        assert!(resolve_worktree(&path, "missing").await.is_err());
+        git_worktree_remove(path.clone(), wt_str, false)
+            .await
+            .unwrap();
+        assert_eq!(git_worktree_list(path).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prune_missing_worktree() {
+        let dir = init_repo(&[("a.txt", "a\n")]);
+        let path = repo_path(&dir);
+        let parent = tempfile::tempdir().unwrap();
+        let wt_path = parent.path().join("gone");
+        git(
+            dir.path(),
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "--detach",
+                &wt_path.to_string_lossy(),
+            ],
+        );
+        std::fs::remove_dir_all(&wt_path).unwrap();
+
+        let pruned = git_worktree_prune(path.clone()).await.unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(git_worktree_list(path).await.unwrap().len(), 1);
+    }
+}
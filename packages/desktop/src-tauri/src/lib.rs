@@ -15,9 +15,16 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_deep_link::init())
+        .manage(commands::remote::RemoteOperations::default())
+        .manage(commands::watcher::WorkspaceWatchers::default())
+        .manage(commands::config_state::ConfigState::default())
         .setup(|app| {
             tray::create_tray(app.handle())?;
 
+            // Notify the UI of config file edits; without the watch, reads
+            // still pick up changes, just without events
+            let _ = commands::config_state::start_config_watch(app.handle());
+
             // Register deep link handler
             let handle = app.handle().clone();
             app.deep_link().on_open_url(move |event| {
@@ -33,23 +40,75 @@ pub fn run() {
             commands::filesystem::file_exists,
             commands::filesystem::get_home_dir,
             commands::workspace::detect_workspaces,
+            commands::workspace::scan_workspaces,
             commands::workspace::get_workspace_info,
+            commands::workspace::get_workspace_tree,
+            commands::workspace::get_workspace_registry,
             commands::workspace::open_in_terminal,
             commands::workspace::open_in_editor,
+            commands::workspace_settings::resolve_workspace_settings,
+            commands::watcher::watch_workspace,
+            commands::watcher::unwatch_workspace,
             commands::server::detect_local_server,
             commands::server::health_check,
             commands::git::git_status,
-            commands::git::git_log,
+            commands::log::git_log,
             commands::git::git_diff,
-            commands::git::git_branch_list,
             commands::git::git_commit,
-            commands::git::git_checkout,
+            commands::branch::git_branch_list,
+            commands::branch::git_checkout,
+            commands::branch::git_branch_create,
+            commands::branch::git_branch_rename,
+            commands::branch::git_branch_delete,
+            commands::branch::git_branch_set_upstream,
+            commands::git::git_stage,
+            commands::git::git_unstage,
+            commands::git::git_discard,
+            commands::git::git_stage_hunk,
+            commands::git::git_unstage_hunk,
+            commands::git::git_discard_hunk,
+            commands::git::git_stash_list,
+            commands::git::git_stash_push,
+            commands::git::git_stash_apply,
+            commands::git::git_stash_pop,
+            commands::git::git_stash_drop,
+            commands::git::git_stash_show,
+            commands::diff::git_diff_files,
+            commands::repo::git_submodule_list,
+            commands::worktree::git_worktree_list,
+            commands::worktree::git_worktree_add,
+            commands::worktree::git_worktree_lock,
+            commands::worktree::git_worktree_unlock,
+            commands::worktree::git_worktree_prune,
+            commands::worktree::git_worktree_remove,
+            commands::merge::git_operation_state,
+            commands::merge::git_conflict_list,
+            commands::merge::git_mark_resolved,
+            commands::merge::git_resolve_take,
+            commands::merge::git_operation_continue,
+            commands::merge::git_operation_abort,
+            commands::remote::git_remote_list,
+            commands::remote::git_fetch,
+            commands::remote::git_pull,
+            commands::remote::git_push,
+            commands::remote::git_remote_cancel,
             commands::ai_cli::detect_ai_tools,
             commands::ai_cli::launch_ai_cli,
             commands::ai_cli::inject_context,
             commands::config::load_ditloop_config,
             commands::config::get_git_identity,
+            commands::config_edit::add_profile,
+            commands::config_edit::update_profile,
+            commands::config_edit::remove_profile,
+            commands::config_edit::add_workspace,
+            commands::config_edit::update_workspace,
+            commands::config_edit::remove_workspace,
+            commands::config_validate::validate_ditloop_config,
+            commands::config_migrate::migrate_ditloop_config,
             commands::config::switch_git_profile,
+            commands::identity::git_check_identity,
+            commands::identity::git_identity_source,
+            commands::ssh::apply_profile_ssh,
             tray::update_tray_counts,
             notifications::send_notification,
             notifications::check_notification_permission,
@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type { Profile } from '@ditloop/core';
 
 /** Config load result from Rust. */
@@ -7,6 +8,48 @@ interface ConfigLoadResult {
   config: RustConfig;
   configPath: string;
   exists: boolean;
+  diagnostics: ConfigDiagnostic[];
+  /** The file uses an older layout; `migrate_ditloop_config` rewrites it. */
+  needsMigration: boolean;
+}
+
+/** Keys of entries that changed between two loads of the config. */
+export interface ConfigEntryChanges {
+  added: string[];
+  removed: string[];
+  modified: string[];
+}
+
+/** Payload of the `config:changed` event, pushed when the file changes on disk. */
+export interface ConfigChangedEvent extends ConfigLoadResult {
+  /** Profiles by key. */
+  profiles: ConfigEntryChanges;
+  /** Workspaces by name. */
+  workspaces: ConfigEntryChanges;
+}
+
+/** A problem found in the config file, positioned for inline display. */
+export interface ConfigDiagnostic {
+  severity: 'error' | 'warning';
+  code:
+    | 'syntax'
+    | 'invalid_value'
+    | 'unknown_key'
+    | 'unknown_profile'
+    | 'duplicate_profile_name'
+    | 'duplicate_workspace_name'
+    | 'duplicate_workspace_path'
+    | 'invalid_email'
+    | 'missing_path'
+    | 'invalid_type'
+    | 'outdated_layout'
+    | 'unsupported_version';
+  message: string;
+  /** Location in the config, e.g. `workspaces[1].profile`. */
+  path: string;
+  /** 1-based line, when the problem could be located. */
+  line: number | null;
+  column: number | null;
 }
 
 /**
@@ -17,6 +60,8 @@ interface ConfigLoadResult {
 interface RustConfig {
   profiles: Record<string, Profile>;
   workspaces: RustWorkspace[];
+  /** Repositories whose settings files may also set the editor and AI tool. */
+  trustedRepos?: string[];
 }
 
 /** Workspace entry as returned by Rust serde (flat, not discriminated union). */
@@ -24,17 +69,34 @@ export interface RustWorkspace {
   name: string;
   path: string;
   type: string;
+  /** Profile key, empty when the workspace has none. */
   profile: string;
   aidf: boolean;
+  /**
+   * Per-workspace settings; a repository's `.ditloop.yml` can set
+   * `emailDomain` and `protectedBranches`, and the rest once it is listed
+   * in `trustedRepos`.
+   */
+  emailDomain?: string | null;
+  aiTool?: string | null;
+  aiArgs?: string[] | null;
+  protectedBranches?: string[] | null;
+  editor?: string | null;
 }
 
-/** Load and manage DitLoop configuration from ~/.ditloop/config.yml. */
+/**
+ * Load and manage DitLoop configuration from `DITLOOP_CONFIG`,
+ * ~/.ditloop/config.yml (or .yaml) or $XDG_CONFIG_HOME/ditloop.
+ */
 export function useConfig() {
   const [config, setConfig] = useState<RustConfig | null>(null);
   const [configPath, setConfigPath] = useState<string>('');
   const [configExists, setConfigExists] = useState(true);
+  const [diagnostics, setDiagnostics] = useState<ConfigDiagnostic[]>([]);
+  const [needsMigration, setNeedsMigration] = useState(false);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
+  const [lastChange, setLastChange] = useState<ConfigChangedEvent | null>(null);
 
   const reload = useCallback(async () => {
     setLoading(true);
@@ -43,6 +105,8 @@ export function useConfig() {
       setConfig(result.config);
       setConfigPath(result.configPath);
       setConfigExists(result.exists);
+      setDiagnostics(result.diagnostics);
+      setNeedsMigration(result.needsMigration);
       setError(null);
     } catch (e) {
       setError(String(e));
@@ -55,5 +119,38 @@ export function useConfig() {
     reload();
   }, [reload]);
 
-  return { config, configPath, configExists, loading, error, reload };
+  useEffect(() => {
+    const unlisten = listen<ConfigChangedEvent>('config:changed', (event) => {
+      const change = event.payload;
+      setConfig(change.config);
+      setConfigPath(change.configPath);
+      setConfigExists(change.exists);
+      setDiagnostics(change.diagnostics);
+      setNeedsMigration(change.needsMigration);
+      setLastChange(change);
+      setError(null);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const migrate = useCallback(async () => {
+    await invoke('migrate_ditloop_config');
+    await reload();
+  }, [reload]);
+
+  return {
+    config,
+    configPath,
+    configExists,
+    diagnostics,
+    needsMigration,
+    lastChange,
+    loading,
+    error,
+    reload,
+    migrate,
+  };
 }
@@ -24,12 +24,15 @@ export function useAiTools() {
   return { tools, loading };
 }
 
-/** Launch an AI CLI tool in a new terminal window. */
+/**
+ * Launch an AI CLI tool in a new terminal window. Without a tool the
+ * workspace's `aiTool` is launched; without args, its `aiArgs` are used.
+ */
 export function useLaunchAiCli() {
   const [launching, setLaunching] = useState(false);
 
   const launch = useCallback(
-    async (tool: string, workspacePath: string, args: string[] = []) => {
+    async (tool: string | undefined, workspacePath: string, args?: string[]) => {
       setLaunching(true);
       try {
         const pid = await invoke<number>('launch_ai_cli', {
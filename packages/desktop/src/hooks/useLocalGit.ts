@@ -20,6 +20,12 @@ interface GitCommit {
   date: string;
 }
 
+/** A page of git log from Rust backend. */
+interface GitLogPage {
+  commits: GitCommit[];
+  next_cursor: unknown | null;
+}
+
 /** Git branch from Rust backend. */
 interface GitBranch {
   name: string;
@@ -69,8 +75,8 @@ export function useGitLog(path: string | undefined, count = 20) {
   useEffect(() => {
     if (!path) return;
     setLoading(true);
-    invoke<GitCommit[]>('git_log', { workspacePath: path, count })
-      .then(setData)
+    invoke<GitLogPage>('git_log', { workspacePath: path, count })
+      .then((page) => setData(page.commits))
       .finally(() => setLoading(false));
   }, [path, count]);
 